@@ -0,0 +1,31 @@
+//! Compares flushing 50k entries with the whole batch written in one call
+//! against writing each line as soon as it's rendered.
+//!
+//! Run with `cargo bench --bench batch`.
+
+use simple_logger::Logger;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+const ENTRIES: usize = 50_000;
+
+fn flush(path: PathBuf, batch: bool) -> Duration {
+    let mut logger = Logger::builder()
+        .file_path(path)
+        .build()
+        .with_batched_writes(batch);
+    for i in 0..ENTRIES {
+        logger.info(format!("benchmark entry {i}"));
+    }
+    let start = Instant::now();
+    logger.flush().unwrap();
+    start.elapsed()
+}
+
+fn main() {
+    let dir = tempfile::tempdir().unwrap();
+    let per_entry = flush(dir.path().join("per-entry.log"), false);
+    println!("one write per entry: {:?}", per_entry);
+    let batched = flush(dir.path().join("batched.log"), true);
+    println!("one write per flush: {:?}", batched);
+}
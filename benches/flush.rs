@@ -0,0 +1,35 @@
+//! Compares flushing 100k entries through the logger with writing the same
+//! lines straight to an unbuffered `File`, one `writeln!` (and syscall) each.
+//!
+//! Run with `cargo bench --bench flush`.
+
+// The `&format!(..)` call site predates owned messages, and checks it still compiles.
+#![allow(clippy::needless_borrows_for_generic_args)]
+
+use simple_logger::Logger;
+use std::fs::File;
+use std::io::Write;
+use std::time::Instant;
+
+const ENTRIES: usize = 100_000;
+
+fn main() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let unbuffered = dir.path().join("unbuffered.log");
+    let start = Instant::now();
+    let mut file = File::create(&unbuffered).unwrap();
+    for i in 0..ENTRIES {
+        writeln!(file, "[2024-06-01 12:00:00] Info: benchmark entry {i}").unwrap();
+    }
+    println!("unbuffered writeln!: {:?}", start.elapsed());
+
+    let buffered = dir.path().join("buffered.log");
+    let mut logger = Logger::builder().file_path(buffered).build();
+    for i in 0..ENTRIES {
+        logger.info(&format!("benchmark entry {i}"));
+    }
+    let start = Instant::now();
+    logger.flush().unwrap();
+    println!("Logger::flush:       {:?}", start.elapsed());
+}
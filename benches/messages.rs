@@ -0,0 +1,35 @@
+//! Compares logging 1M static literals stored without being copied, as
+//! `Cow::Borrowed` or through the macros, with logging a copy of each, as a
+//! plain `&str` makes.
+//!
+//! Run with `cargo bench --bench messages`.
+
+use simple_logger::{info, Logger};
+use std::borrow::Cow;
+use std::time::Instant;
+
+const ENTRIES: usize = 1_000_000;
+
+fn main() {
+    let mut logger = Logger::builder().build();
+    let start = Instant::now();
+    for _ in 0..ENTRIES {
+        logger.info("request handled");
+    }
+    println!("copied &str:    {:?}", start.elapsed());
+    logger.clear();
+
+    let start = Instant::now();
+    for _ in 0..ENTRIES {
+        logger.info(Cow::Borrowed("request handled"));
+    }
+    println!("Cow::Borrowed:  {:?}", start.elapsed());
+    logger.clear();
+
+    let start = Instant::now();
+    for _ in 0..ENTRIES {
+        info!(logger, "request handled");
+    }
+    println!("info! literal:  {:?}", start.elapsed());
+    logger.clear();
+}
@@ -0,0 +1,169 @@
+use crate::{
+    thread_name, Clock, IntoMessage, LogEntry, LogLevel, Logger, LoggerError, STATIC_MAX_LEVEL,
+};
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+/// Hands entries to a background thread that buffers and flushes them, so
+/// logging never waits on the disk.
+///
+/// Entries are flushed in batches, whenever the thread catches up with the
+/// queue. Call [`shutdown`](Self::shutdown) to write what's left and get the
+/// last flush error, if any; dropping the logger does the same but discards
+/// the error.
+pub struct AsyncLogger {
+    sender: Option<Queue>,
+    worker: Option<JoinHandle<Result<(), LoggerError>>>,
+    thread_info: bool,
+    clock: Arc<dyn Clock>,
+    overflow: Overflow,
+    dropped: AtomicU64,
+}
+
+/// What a bounded [`AsyncLogger`] does with entries logged while its queue is full.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Wait for the background thread to make room.
+    #[default]
+    Block,
+    /// Drop the entry. The number dropped is logged on shutdown.
+    DropNewest,
+}
+
+enum Queue {
+    Unbounded(Sender<LogEntry>),
+    Bounded(SyncSender<LogEntry>),
+}
+
+impl AsyncLogger {
+    /// Moves `logger` to a new background thread, with a queue that grows as needed.
+    pub fn new(logger: Logger) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        AsyncLogger::spawn(logger, Queue::Unbounded(sender), receiver)
+    }
+
+    /// Like [`new`](Self::new), but queues at most `capacity` entries; see
+    /// [`with_overflow`](Self::with_overflow) for what happens past that.
+    pub fn with_capacity(logger: Logger, capacity: usize) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        AsyncLogger::spawn(logger, Queue::Bounded(sender), receiver)
+    }
+
+    fn spawn(logger: Logger, sender: Queue, receiver: Receiver<LogEntry>) -> Self {
+        let thread_info = logger.thread_info;
+        let clock = Arc::clone(&logger.clock);
+        let worker = thread::Builder::new()
+            .name(String::from("simple_logger"))
+            .spawn(move || run(logger, receiver))
+            .expect("failed to spawn the logging thread");
+        AsyncLogger {
+            sender: Some(sender),
+            worker: Some(worker),
+            thread_info,
+            clock,
+            overflow: Overflow::default(),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Sets what happens when a bounded queue is full. Defaults to [`Overflow::Block`].
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Logs `message` at `level`, for when the level is only known at runtime.
+    pub fn log(&self, level: LogLevel, message: impl IntoMessage) {
+        if level < STATIC_MAX_LEVEL {
+            return;
+        }
+        let mut entry = LogEntry::new(self.clock.now(), level, message);
+        if self.thread_info {
+            entry.thread = Some(thread_name());
+        }
+        self.send(entry);
+    }
+
+    fn send(&self, entry: LogEntry) {
+        // Sending only fails once the worker is gone, and then there's nowhere left to log to.
+        match &self.sender {
+            Some(Queue::Unbounded(sender)) => {
+                let _ = sender.send(entry);
+            }
+            Some(Queue::Bounded(sender)) if self.overflow == Overflow::DropNewest => {
+                if let Err(TrySendError::Full(_)) = sender.try_send(entry) {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            Some(Queue::Bounded(sender)) => {
+                let _ = sender.send(entry);
+            }
+            None => {}
+        }
+    }
+
+    pub fn trace(&self, message: impl IntoMessage) {
+        self.log(LogLevel::Trace, message);
+    }
+
+    pub fn debug(&self, message: impl IntoMessage) {
+        self.log(LogLevel::Debug, message);
+    }
+
+    pub fn info(&self, message: impl IntoMessage) {
+        self.log(LogLevel::Info, message);
+    }
+
+    pub fn warn(&self, message: impl IntoMessage) {
+        self.log(LogLevel::Warn, message);
+    }
+
+    pub fn error(&self, message: impl IntoMessage) {
+        self.log(LogLevel::Error, message);
+    }
+
+    /// Waits for the background thread to write every queued entry.
+    pub fn shutdown(mut self) -> Result<(), LoggerError> {
+        self.stop()
+    }
+
+    fn stop(&mut self) -> Result<(), LoggerError> {
+        let dropped = self.dropped.swap(0, Ordering::Relaxed);
+        if dropped > 0 {
+            let message = format!("dropped {} entries while the queue was full", dropped);
+            self.overflow = Overflow::Block;
+            self.send(LogEntry::new(self.clock.now(), LogLevel::Warn, message));
+        }
+        // Closing the channel lets the worker drain the queue and exit.
+        drop(self.sender.take());
+        match self.worker.take() {
+            Some(worker) => worker
+                .join()
+                .unwrap_or_else(|_| Err(io::Error::other("the logging thread panicked").into())),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for AsyncLogger {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+fn run(mut logger: Logger, receiver: Receiver<LogEntry>) -> Result<(), LoggerError> {
+    let mut result = Ok(());
+    while let Ok(entry) = receiver.recv() {
+        logger.adopt(entry);
+        while let Ok(entry) = receiver.try_recv() {
+            logger.adopt(entry);
+        }
+        if let Err(err) = logger.flush() {
+            result = Err(err);
+        }
+    }
+    result
+}
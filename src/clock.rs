@@ -0,0 +1,35 @@
+use chrono::{DateTime, FixedOffset, Local};
+
+/// Source of the timestamps stamped on new entries, see [`Logger::with_clock`](crate::Logger::with_clock).
+///
+/// ```
+/// use chrono::{DateTime, FixedOffset};
+/// use simple_logger::{Clock, Logger};
+///
+/// struct Frozen(DateTime<FixedOffset>);
+///
+/// impl Clock for Frozen {
+///     fn now(&self) -> DateTime<FixedOffset> {
+///         self.0
+///     }
+/// }
+///
+/// let noon = DateTime::parse_from_rfc3339("2024-06-01T12:00:00+02:00").unwrap();
+/// let mut logger = Logger::builder().build().with_clock(Box::new(Frozen(noon)));
+/// logger.info("at noon");
+/// assert_eq!(logger.iter().next().unwrap().timestamp(), noon);
+/// # logger.clear();
+/// ```
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<FixedOffset>;
+}
+
+/// The real local time. Used unless another [`Clock`] is set.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        Local::now().fixed_offset()
+    }
+}
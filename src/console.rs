@@ -0,0 +1,154 @@
+use crate::format::{self, Layout};
+use crate::{LogEntry, LogLevel, Sink};
+use std::env;
+use std::io::{self, IsTerminal, Write};
+
+/// Echoes entries to the terminal as they are logged, optionally colored by level.
+///
+/// Entries at or above the stderr threshold go to the error stream and the
+/// rest to the output stream, each in the order they were logged.
+///
+/// Colors are never used when `NO_COLOR` is set, or when the standard stream
+/// being written to isn't a terminal.
+pub struct ConsoleSink {
+    out: Stream,
+    err: Option<Stream>,
+    stderr_threshold: LogLevel,
+    color: bool,
+    sanitize: bool,
+}
+
+struct Stream {
+    writer: Box<dyn Write + Send>,
+    color_supported: bool,
+}
+
+impl Stream {
+    fn new(writer: impl Write + Send + 'static, is_terminal: bool) -> Self {
+        Stream {
+            writer: Box::new(writer),
+            color_supported: is_terminal && env::var_os("NO_COLOR").is_none(),
+        }
+    }
+}
+
+impl ConsoleSink {
+    /// Writes everything to stdout.
+    pub fn stdout() -> Self {
+        let out = Stream::new(io::stdout(), io::stdout().is_terminal());
+        ConsoleSink::with_streams(out, None, LogLevel::Default)
+    }
+
+    /// Writes everything to stderr, until [`with_stderr_threshold`](Self::with_stderr_threshold)
+    /// moves the less severe levels to stdout.
+    pub fn stderr() -> Self {
+        ConsoleSink::split().with_stderr_threshold(LogLevel::Default)
+    }
+
+    /// Writes errors to stderr and everything else to stdout.
+    pub fn split() -> Self {
+        let out = Stream::new(io::stdout(), io::stdout().is_terminal());
+        let err = Stream::new(io::stderr(), io::stderr().is_terminal());
+        ConsoleSink::with_streams(out, Some(err), LogLevel::Error)
+    }
+
+    /// Writes to an arbitrary stream, skipping terminal detection.
+    pub fn writer(out: impl Write + Send + 'static) -> Self {
+        ConsoleSink::with_streams(Stream::new(out, true), None, LogLevel::Default)
+    }
+
+    /// Like [`split`](Self::split), but with arbitrary streams.
+    pub fn writers(out: impl Write + Send + 'static, err: impl Write + Send + 'static) -> Self {
+        let out = Stream::new(out, true);
+        let err = Stream::new(err, true);
+        ConsoleSink::with_streams(out, Some(err), LogLevel::Error)
+    }
+
+    fn with_streams(out: Stream, err: Option<Stream>, stderr_threshold: LogLevel) -> Self {
+        ConsoleSink {
+            out,
+            err,
+            stderr_threshold,
+            color: false,
+            sanitize: true,
+        }
+    }
+
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Escapes control characters in messages, as
+    /// [`Logger::with_sanitize`](crate::Logger::with_sanitize) does for the log
+    /// file. On by default.
+    pub fn with_sanitize(mut self, sanitize: bool) -> Self {
+        self.sanitize = sanitize;
+        self
+    }
+
+    /// Sends entries at or above `level` to the error stream. Has no effect on
+    /// sinks with a single stream.
+    pub fn with_stderr_threshold(mut self, level: LogLevel) -> Self {
+        self.stderr_threshold = level;
+        self
+    }
+
+    pub(crate) fn set_color(&mut self, color: bool) {
+        self.color = color;
+    }
+
+    pub(crate) fn set_stderr_threshold(&mut self, level: LogLevel) {
+        self.stderr_threshold = level;
+    }
+}
+
+impl Sink for ConsoleSink {
+    fn write_entry(&mut self, entry: &LogEntry) -> io::Result<()> {
+        let stream = match &mut self.err {
+            Some(err) if entry.level >= self.stderr_threshold => err,
+            _ => &mut self.out,
+        };
+        let message = if self.sanitize {
+            format::sanitize(&entry.message)
+        } else {
+            None
+        };
+        let cleaned;
+        let entry = match message {
+            Some(message) => {
+                cleaned = LogEntry {
+                    message: message.into(),
+                    ..entry.clone()
+                };
+                &cleaned
+            }
+            None => entry,
+        };
+        let line = format::text(entry, &Layout::default());
+        match color_code(entry.level) {
+            Some(code) if self.color && stream.color_supported => {
+                writeln!(stream.writer, "\x1b[{}m{}\x1b[0m", code, line)
+            }
+            _ => writeln!(stream.writer, "{}", line),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(err) = &mut self.err {
+            err.writer.flush()?;
+        }
+        self.out.writer.flush()
+    }
+}
+
+fn color_code(level: LogLevel) -> Option<&'static str> {
+    match level {
+        LogLevel::Default => None,
+        LogLevel::Trace => Some("90"),
+        LogLevel::Debug => Some("36"),
+        LogLevel::Info => Some("32"),
+        LogLevel::Warn => Some("33"),
+        LogLevel::Error => Some("31"),
+    }
+}
@@ -0,0 +1,68 @@
+use crate::ParseLevelError;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Why flushing (or another logger operation) failed.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LoggerError {
+    /// Opening or writing a log file failed.
+    Io(io::Error),
+    /// A log file path points at an existing directory.
+    IsDirectory(PathBuf),
+    /// Moving, compressing or deleting backups of a full log file failed.
+    Rotation(io::Error),
+    /// A [`Sink`](crate::Sink) failed to write or flush an entry.
+    Sink(io::Error),
+    /// A level name couldn't be parsed.
+    InvalidLevel(ParseLevelError),
+}
+
+impl fmt::Display for LoggerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoggerError::Io(err) => write!(f, "failed to write the log file: {}", err),
+            LoggerError::IsDirectory(path) => {
+                write!(f, "log file path {:?} is a directory", path)
+            }
+            LoggerError::Rotation(err) => write!(f, "failed to rotate the log file: {}", err),
+            LoggerError::Sink(err) => write!(f, "sink failed: {}", err),
+            LoggerError::InvalidLevel(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for LoggerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoggerError::Io(err) | LoggerError::Rotation(err) | LoggerError::Sink(err) => Some(err),
+            LoggerError::InvalidLevel(err) => Some(err),
+            LoggerError::IsDirectory(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for LoggerError {
+    fn from(err: io::Error) -> Self {
+        LoggerError::Io(err)
+    }
+}
+
+impl From<ParseLevelError> for LoggerError {
+    fn from(err: ParseLevelError) -> Self {
+        LoggerError::InvalidLevel(err)
+    }
+}
+
+/// Lets `?` on a logger call work in functions returning `io::Result`.
+impl From<LoggerError> for io::Error {
+    fn from(err: LoggerError) -> Self {
+        match err {
+            LoggerError::Io(err) | LoggerError::Rotation(err) | LoggerError::Sink(err) => err,
+            LoggerError::IsDirectory(_) => io::Error::new(io::ErrorKind::IsADirectory, err),
+            LoggerError::InvalidLevel(_) => io::Error::new(io::ErrorKind::InvalidInput, err),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{LogLevel, Logger, SharedLogger};
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+use std::borrow::Cow;
+
+/// Adapter registered by [`Logger::init`] so the `log` crate macros end up in a [`Logger`].
+///
+/// Entries are only written when `log::logger().flush()` is called, since the
+/// global logger is never dropped.
+struct LogFacade {
+    logger: SharedLogger,
+    threshold: LogLevel,
+}
+
+impl From<Level> for LogLevel {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::Error => LogLevel::Error,
+            Level::Warn => LogLevel::Warn,
+            Level::Info => LogLevel::Info,
+            Level::Debug => LogLevel::Debug,
+            Level::Trace => LogLevel::Trace,
+        }
+    }
+}
+
+fn level_filter(level: LogLevel) -> LevelFilter {
+    match level {
+        LogLevel::Default | LogLevel::Trace => LevelFilter::Trace,
+        LogLevel::Debug => LevelFilter::Debug,
+        LogLevel::Info => LevelFilter::Info,
+        LogLevel::Warn => LevelFilter::Warn,
+        LogLevel::Error => LevelFilter::Error,
+    }
+}
+
+impl Log for LogFacade {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        LogLevel::from(metadata.level()) >= self.threshold
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        // Messages without arguments are `&'static str` and needn't be copied.
+        let message = match record.args().as_str() {
+            Some(message) => Cow::Borrowed(message),
+            None => Cow::Owned(record.args().to_string()),
+        };
+        self.logger.lock().log(record.level().into(), message);
+    }
+
+    fn flush(&self) {
+        // `Log::flush` can't report failures.
+        let _ = self.logger.flush();
+    }
+}
+
+impl Logger {
+    /// Registers this logger as the global `log` crate logger.
+    pub fn init(self) -> Result<(), SetLoggerError> {
+        let threshold = self.log_level();
+        log::set_boxed_logger(Box::new(LogFacade {
+            logger: SharedLogger::new(self),
+            threshold,
+        }))?;
+        log::set_max_level(level_filter(threshold));
+        Ok(())
+    }
+}
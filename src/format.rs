@@ -0,0 +1,462 @@
+use crate::{LevelCounts, LogEntry, LogLevel, DEFAULT_TIMESTAMP_FORMAT};
+use chrono::{DateTime, Local};
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Layout used when writing entries to the log file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `[timestamp] [tag] LEVEL: message key=value ... (src/file.rs:42)`
+    #[default]
+    Text,
+    /// One JSON object per line with `timestamp` (RFC 3339), `level`, numeric
+    /// `severity` and `message` fields, plus a `fields` object when the entry
+    /// has structured fields.
+    Json,
+    /// Like [`Json`](Self::Json), for tools that tail the file as a stream.
+    /// With [`with_header`](crate::Logger::with_header), new files start with a
+    /// metadata line holding the `app` name, `pid` and `started` time.
+    Ndjson,
+    /// `ts=... level=info severity=6 msg="..." key=value ...`, quoting values that
+    /// contain spaces, quotes or `=`.
+    Logfmt,
+    /// Each flush as a single JSON array of entries like [`Json`](Self::Json)'s,
+    /// written on one line. Flushing to a file again appends another array on
+    /// the next line, so flush once (or to a fresh file each time, or with
+    /// [`flush_to_writer`](crate::Logger::flush_to_writer)) when consumers
+    /// expect the file to hold one array. Level routing would split the array
+    /// across files, so don't combine the two.
+    JsonArray,
+    /// `timestamp,level,message` records, quoting fields that contain commas,
+    /// quotes or line breaks. New files start with a header row.
+    Csv,
+}
+
+/// Terminator written after each line of the log file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`
+    CrLf,
+    /// `\r\n` on Windows, `\n` everywhere else.
+    Native,
+}
+
+impl LineEnding {
+    pub(crate) const fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Native if cfg!(windows) => "\r\n",
+            LineEnding::Native => "\n",
+        }
+    }
+}
+
+/// Fractional seconds shown by the default timestamp format.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TimePrecision {
+    /// `2024-06-01 12:00:00`
+    #[default]
+    Seconds,
+    /// `2024-06-01 12:00:00.123`
+    Millis,
+    /// `2024-06-01 12:00:00.123456`
+    Micros,
+    /// `2024-06-01 12:00:00.123456789`
+    Nanos,
+}
+
+impl TimePrecision {
+    pub(crate) const fn timestamp_format(self) -> &'static str {
+        match self {
+            TimePrecision::Seconds => "%Y-%m-%d %H:%M:%S",
+            TimePrecision::Millis => "%Y-%m-%d %H:%M:%S%.3f",
+            TimePrecision::Micros => "%Y-%m-%d %H:%M:%S%.6f",
+            TimePrecision::Nanos => "%Y-%m-%d %H:%M:%S%.9f",
+        }
+    }
+}
+
+/// How timestamps are written, see [`Logger::with_timestamp_mode`](crate::Logger::with_timestamp_mode).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampMode {
+    /// With the timestamp format in text and CSV output, and as RFC 3339 in
+    /// JSON and logfmt.
+    #[default]
+    Formatted,
+    /// As the number of units since the Unix epoch, e.g. `1717245045123`, in
+    /// every format. JSON output writes it as a number.
+    Epoch(TimeUnit),
+    /// As the time elapsed since the logger was created, e.g. `+0.123s`, in
+    /// every format. Handy for profiling.
+    Relative,
+}
+
+/// Unit of [`TimestampMode::Epoch`] timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeUnit {
+    Seconds,
+    Millis,
+    Micros,
+}
+
+pub(crate) type LevelLabels = dyn Fn(&LogLevel) -> &str + Send + Sync;
+pub(crate) type SeverityMap = dyn Fn(&LogLevel) -> u8 + Send + Sync;
+pub(crate) type Formatter = dyn Fn(&LogEntry) -> String + Send + Sync;
+pub(crate) type Redactor = dyn Fn(&str) -> String + Send + Sync;
+
+/// Builds a redactor for [`Logger::with_redactor`](crate::Logger::with_redactor)
+/// that replaces every occurrence of each pattern with `***`.
+///
+/// ```
+/// let redact = simple_logger::redact_patterns(&["hunter2"]);
+/// assert_eq!(redact("password=hunter2"), "password=***");
+/// ```
+pub fn redact_patterns(patterns: &[&str]) -> impl Fn(&str) -> String + Send + Sync + 'static {
+    let patterns: Vec<String> = patterns
+        .iter()
+        .filter(|pattern| !pattern.is_empty())
+        .map(|pattern| pattern.to_string())
+        .collect();
+    move |message| {
+        let mut message = message.to_string();
+        for pattern in &patterns {
+            message = message.replace(pattern.as_str(), "***");
+        }
+        message
+    }
+}
+
+/// Where entries come from, shown with [`Logger::with_origin`](crate::Logger::with_origin).
+pub(crate) struct Origin {
+    pub(crate) hostname: String,
+    pub(crate) pid: u32,
+}
+
+impl Origin {
+    pub(crate) fn current() -> Self {
+        Origin {
+            hostname: gethostname::gethostname().to_string_lossy().into_owned(),
+            pid: std::process::id(),
+        }
+    }
+}
+
+/// Logger settings that affect how a single entry is rendered.
+pub(crate) struct Layout<'a> {
+    pub(crate) timestamp_format: &'a str,
+    pub(crate) level_labels: Option<&'a LevelLabels>,
+    pub(crate) origin: Option<&'a Origin>,
+    pub(crate) timestamp_mode: TimestampMode,
+    pub(crate) severity_map: Option<&'a SeverityMap>,
+    pub(crate) started: Option<DateTime<Local>>,
+}
+
+impl Default for Layout<'_> {
+    fn default() -> Self {
+        Layout {
+            timestamp_format: DEFAULT_TIMESTAMP_FORMAT,
+            level_labels: None,
+            origin: None,
+            timestamp_mode: TimestampMode::Formatted,
+            severity_map: None,
+            started: None,
+        }
+    }
+}
+
+impl Layout<'_> {
+    fn epoch(&self, entry: &LogEntry) -> Option<i64> {
+        match self.timestamp_mode {
+            TimestampMode::Formatted | TimestampMode::Relative => None,
+            TimestampMode::Epoch(TimeUnit::Seconds) => Some(entry.timestamp.timestamp()),
+            TimestampMode::Epoch(TimeUnit::Millis) => Some(entry.timestamp.timestamp_millis()),
+            TimestampMode::Epoch(TimeUnit::Micros) => Some(entry.timestamp.timestamp_micros()),
+        }
+    }
+
+    /// Time since `started`, in seconds with millisecond precision.
+    fn relative(&self, entry: &LogEntry) -> Option<String> {
+        let started = self
+            .started
+            .filter(|_| self.timestamp_mode == TimestampMode::Relative)?;
+        let millis = entry
+            .timestamp
+            .signed_duration_since(started)
+            .num_milliseconds();
+        let sign = if millis < 0 { '-' } else { '+' };
+        let millis = millis.unsigned_abs();
+        Some(format!("{}{}.{:03}s", sign, millis / 1000, millis % 1000))
+    }
+
+    /// The timestamp for text and CSV output.
+    fn formatted_timestamp(&self, entry: &LogEntry) -> String {
+        if let Some(relative) = self.relative(entry) {
+            return relative;
+        }
+        match self.epoch(entry) {
+            Some(epoch) => epoch.to_string(),
+            None => entry.timestamp.format(self.timestamp_format).to_string(),
+        }
+    }
+
+    /// The timestamp for JSON and logfmt output.
+    fn rfc3339_timestamp(&self, entry: &LogEntry) -> String {
+        if let Some(relative) = self.relative(entry) {
+            return relative;
+        }
+        match self.epoch(entry) {
+            Some(epoch) => epoch.to_string(),
+            None => entry.timestamp.to_rfc3339(),
+        }
+    }
+
+    fn severity(&self, level: &LogLevel) -> u8 {
+        match self.severity_map {
+            Some(severity) => severity(level),
+            None => level.syslog_severity(),
+        }
+    }
+
+    fn label<'e>(&self, level: &'e LogLevel) -> Cow<'e, str> {
+        match self.level_labels {
+            Some(labels) => Cow::Borrowed(labels(level)),
+            None => Cow::Owned(level.to_string()),
+        }
+    }
+}
+
+pub(crate) fn text(entry: &LogEntry, layout: &Layout) -> String {
+    let mut line = format!("[{}] ", layout.formatted_timestamp(entry));
+    if let Some(origin) = layout.origin {
+        let _ = write!(line, "[{}:{}] ", origin.hostname, origin.pid);
+    }
+    if let Some(thread) = &entry.thread {
+        let _ = write!(line, "[{}] ", thread);
+    }
+    if let Some(tag) = &entry.tag {
+        let _ = write!(line, "[{}] ", tag);
+    }
+    let _ = write!(line, "{}: {}", layout.label(&entry.level), entry.message);
+    for (key, value) in &entry.fields {
+        let _ = write!(line, " {}={}", key, value);
+    }
+    if let Some((file, number)) = entry.location {
+        let _ = write!(line, " ({}:{})", file, number);
+    }
+    if let Some(backtrace) = &entry.backtrace {
+        for frame in backtrace.lines() {
+            let _ = write!(line, "\n    {}", frame);
+        }
+    }
+    line
+}
+
+pub(crate) fn json(entry: &LogEntry, layout: &Layout) -> String {
+    let mut line = String::from("{\"timestamp\":");
+    match layout.epoch(entry) {
+        Some(epoch) => {
+            let _ = write!(line, "{}", epoch);
+        }
+        None => push_json_string(&mut line, &layout.rfc3339_timestamp(entry)),
+    }
+    line.push_str(",\"level\":");
+    push_json_string(&mut line, &layout.label(&entry.level));
+    let _ = write!(line, ",\"severity\":{}", layout.severity(&entry.level));
+    line.push_str(",\"message\":");
+    push_json_string(&mut line, &entry.message);
+    if let Some(origin) = layout.origin {
+        line.push_str(",\"hostname\":");
+        push_json_string(&mut line, &origin.hostname);
+        let _ = write!(line, ",\"pid\":{}", origin.pid);
+    }
+    if !entry.fields.is_empty() {
+        line.push_str(",\"fields\":{");
+        for (index, (key, value)) in entry.fields.iter().enumerate() {
+            if index > 0 {
+                line.push(',');
+            }
+            push_json_string(&mut line, key);
+            line.push(':');
+            push_json_string(&mut line, value);
+        }
+        line.push('}');
+    }
+    if let Some(thread) = &entry.thread {
+        line.push_str(",\"thread\":");
+        push_json_string(&mut line, thread);
+    }
+    if let Some(tag) = &entry.tag {
+        line.push_str(",\"tag\":");
+        push_json_string(&mut line, tag);
+    }
+    if let Some((file, number)) = entry.location {
+        line.push_str(",\"location\":");
+        push_json_string(&mut line, &format!("{}:{}", file, number));
+    }
+    if let Some(backtrace) = &entry.backtrace {
+        line.push_str(",\"backtrace\":");
+        push_json_string(&mut line, backtrace);
+    }
+    line.push('}');
+    line
+}
+
+pub(crate) fn logfmt(entry: &LogEntry, layout: &Layout) -> String {
+    let mut line = String::from("ts=");
+    push_logfmt_value(&mut line, &layout.rfc3339_timestamp(entry));
+    line.push_str(" level=");
+    match layout.level_labels {
+        Some(labels) => push_logfmt_value(&mut line, labels(&entry.level)),
+        None => push_logfmt_value(&mut line, &entry.level.to_string().to_ascii_lowercase()),
+    }
+    let _ = write!(line, " severity={}", layout.severity(&entry.level));
+    line.push_str(" msg=");
+    push_logfmt_value(&mut line, &entry.message);
+    if let Some(origin) = layout.origin {
+        line.push_str(" hostname=");
+        push_logfmt_value(&mut line, &origin.hostname);
+        let _ = write!(line, " pid={}", origin.pid);
+    }
+    for (key, value) in &entry.fields {
+        let _ = write!(line, " {}=", key);
+        push_logfmt_value(&mut line, value);
+    }
+    if let Some(thread) = &entry.thread {
+        line.push_str(" thread=");
+        push_logfmt_value(&mut line, thread);
+    }
+    if let Some(tag) = &entry.tag {
+        line.push_str(" tag=");
+        push_logfmt_value(&mut line, tag);
+    }
+    if let Some((file, number)) = entry.location {
+        line.push_str(" location=");
+        push_logfmt_value(&mut line, &format!("{}:{}", file, number));
+    }
+    if let Some(backtrace) = &entry.backtrace {
+        line.push_str(" backtrace=");
+        push_logfmt_value(&mut line, backtrace);
+    }
+    line
+}
+
+fn push_logfmt_value(out: &mut String, value: &str) {
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c == ' ' || c == '=' || c == '"' || c == '\\' || c.is_control());
+    if !needs_quotes {
+        out.push_str(value);
+        return;
+    }
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+pub(crate) const CSV_HEADER: &str = "timestamp,level,message";
+
+pub(crate) fn csv(entry: &LogEntry, layout: &Layout) -> String {
+    let mut line = String::new();
+    push_csv_field(&mut line, &layout.formatted_timestamp(entry));
+    line.push(',');
+    push_csv_field(&mut line, &layout.label(&entry.level));
+    line.push(',');
+    push_csv_field(&mut line, &entry.message);
+    line
+}
+
+fn push_csv_field(out: &mut String, value: &str) {
+    if !value.contains([',', '"', '\n', '\r']) {
+        out.push_str(value);
+        return;
+    }
+    out.push('"');
+    out.push_str(&value.replace('"', "\"\""));
+    out.push('"');
+}
+
+pub(crate) fn ndjson_header(app: &str, pid: u32, started: &DateTime<Local>) -> String {
+    let mut line = String::from("{\"app\":");
+    push_json_string(&mut line, app);
+    let _ = write!(line, ",\"pid\":{},\"started\":", pid);
+    push_json_string(&mut line, &started.to_rfc3339());
+    line.push('}');
+    line
+}
+
+pub(crate) fn session_header(pid: u32, started: &DateTime<Local>) -> String {
+    format!(
+        "# session started {} pid={} os={} version={}",
+        started.to_rfc3339(),
+        pid,
+        std::env::consts::OS,
+        env!("CARGO_PKG_VERSION"),
+    )
+}
+
+pub(crate) fn session_footer(counts: &LevelCounts, elapsed: Duration) -> String {
+    format!(
+        "# session ended: {} entries (default={} trace={} debug={} info={} warn={} error={}) over {:.3}s",
+        counts.total(),
+        counts.default,
+        counts.trace,
+        counts.debug,
+        counts.info,
+        counts.warn,
+        counts.error,
+        elapsed.as_secs_f64(),
+    )
+}
+
+/// Escapes the control characters in `message`, or returns `None` if it has none.
+pub(crate) fn sanitize(message: &str) -> Option<String> {
+    if !message.chars().any(char::is_control) {
+        return None;
+    }
+    let mut clean = String::with_capacity(message.len());
+    for c in message.chars() {
+        match c {
+            '\n' => clean.push_str("\\n"),
+            '\r' => clean.push_str("\\r"),
+            '\t' => clean.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(clean, "\\x{:02x}", c as u32);
+            }
+            c => clean.push(c),
+        }
+    }
+    Some(clean)
+}
+
+pub(crate) fn push_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
@@ -0,0 +1,49 @@
+use crate::{Logger, SharedLogger};
+use std::error::Error;
+use std::fmt;
+use std::sync::OnceLock;
+
+static GLOBAL: OnceLock<SharedLogger> = OnceLock::new();
+
+/// Returned by [`Logger::init_global`] when the global logger is already set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitGlobalError {
+    _private: (),
+}
+
+impl fmt::Display for InitGlobalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the global logger is already initialized")
+    }
+}
+
+impl Error for InitGlobalError {}
+
+impl Logger {
+    /// Installs `logger` as the one returned by [`Logger::global`]. It can only
+    /// be set once; being global, it's never dropped, so flush it explicitly.
+    pub fn init_global(logger: Logger) -> Result<(), InitGlobalError> {
+        GLOBAL
+            .set(SharedLogger::new(logger))
+            .map_err(|_| InitGlobalError { _private: () })
+    }
+
+    /// The logger installed by [`Logger::init_global`].
+    ///
+    /// ```
+    /// use simple_logger::Logger;
+    ///
+    /// Logger::init_global(Logger::new(std::env::temp_dir().join("app.log"))).unwrap();
+    /// Logger::global().lock().info("from anywhere");
+    /// # Logger::global().lock().clear();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the global logger hasn't been initialized.
+    pub fn global() -> &'static SharedLogger {
+        GLOBAL
+            .get()
+            .expect("Logger::init_global must be called before Logger::global")
+    }
+}
@@ -0,0 +1,47 @@
+use crate::Logger;
+use std::ops::{Deref, DerefMut};
+
+/// Flushes the borrowed [`Logger`] when it goes out of scope, see [`Logger::guard`].
+pub struct LoggerGuard<'a> {
+    logger: &'a mut Logger,
+}
+
+impl Logger {
+    /// Borrows the logger until the end of a scope, flushing it when the guard is dropped.
+    ///
+    /// ```
+    /// use simple_logger::Logger;
+    ///
+    /// let mut logger = Logger::new(std::env::temp_dir().join("app.log"));
+    /// {
+    ///     let mut log = logger.guard();
+    ///     log.info("written when `log` goes out of scope");
+    /// }
+    /// assert!(logger.is_empty());
+    /// ```
+    pub fn guard(&mut self) -> LoggerGuard<'_> {
+        LoggerGuard { logger: self }
+    }
+}
+
+impl Deref for LoggerGuard<'_> {
+    type Target = Logger;
+
+    fn deref(&self) -> &Logger {
+        self.logger
+    }
+}
+
+impl DerefMut for LoggerGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Logger {
+        self.logger
+    }
+}
+
+impl Drop for LoggerGuard<'_> {
+    fn drop(&mut self) {
+        if let Err(err) = self.logger.flush() {
+            eprintln!("simple_logger: failed to flush log entries: {}", err);
+        }
+    }
+}
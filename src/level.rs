@@ -0,0 +1,144 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Severity of a log entry, from least to most severe:
+/// `Trace < Debug < Info < Warn < Error`.
+///
+/// `Default` is not a severity of its own; it sorts below every other level, so
+/// as the active threshold it writes every entry.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    #[default]
+    Default,
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// The most verbose level compiled in, set by the `max_level_*` features.
+/// Logging calls below it do nothing, and the optimizer removes them.
+///
+/// With `max_level_info`, for example, this is [`LogLevel::Info`] and
+/// `logger.debug(..)` compiles to nothing. When several are enabled, the most
+/// restrictive wins; with none, every level is kept.
+pub const STATIC_MAX_LEVEL: LogLevel = if cfg!(feature = "max_level_error") {
+    LogLevel::Error
+} else if cfg!(feature = "max_level_warn") {
+    LogLevel::Warn
+} else if cfg!(feature = "max_level_info") {
+    LogLevel::Info
+} else if cfg!(feature = "max_level_debug") {
+    LogLevel::Debug
+} else {
+    LogLevel::Default
+};
+
+impl LogLevel {
+    /// Syslog severity for the level, from `LOG_ERR` (3) to `LOG_DEBUG` (7).
+    /// A sensible map to pass to [`Logger::with_severity_map`](crate::Logger::with_severity_map).
+    pub fn syslog_severity(&self) -> u8 {
+        match self {
+            LogLevel::Error => 3,
+            LogLevel::Warn => 4,
+            LogLevel::Default | LogLevel::Info => 6,
+            LogLevel::Debug | LogLevel::Trace => 7,
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    /// Renders the uppercase label written to log files, e.g. `WARN`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            LogLevel::Default => "DEFAULT",
+            LogLevel::Trace => "TRACE",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        };
+        f.pad(label)
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = ParseLevelError;
+
+    /// Accepts `trace`, `debug`, `info`, `warn`, `error` and `default` in any case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "default" => Ok(LogLevel::Default),
+            "trace" => Ok(LogLevel::Trace),
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "warn" => Ok(LogLevel::Warn),
+            "error" => Ok(LogLevel::Error),
+            _ => Err(ParseLevelError {
+                input: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// Number of entries logged at each [`LogLevel`], as returned by
+/// [`Logger::stats`](crate::Logger::stats).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LevelCounts {
+    pub default: usize,
+    pub trace: usize,
+    pub debug: usize,
+    pub info: usize,
+    pub warn: usize,
+    pub error: usize,
+}
+
+impl LevelCounts {
+    pub fn get(&self, level: LogLevel) -> usize {
+        match level {
+            LogLevel::Default => self.default,
+            LogLevel::Trace => self.trace,
+            LogLevel::Debug => self.debug,
+            LogLevel::Info => self.info,
+            LogLevel::Warn => self.warn,
+            LogLevel::Error => self.error,
+        }
+    }
+
+    /// Sum over every level.
+    pub fn total(&self) -> usize {
+        self.default + self.trace + self.debug + self.info + self.warn + self.error
+    }
+
+    pub(crate) fn add(&mut self, level: LogLevel) {
+        let count = match level {
+            LogLevel::Default => &mut self.default,
+            LogLevel::Trace => &mut self.trace,
+            LogLevel::Debug => &mut self.debug,
+            LogLevel::Info => &mut self.info,
+            LogLevel::Warn => &mut self.warn,
+            LogLevel::Error => &mut self.error,
+        };
+        *count += 1;
+    }
+}
+
+/// Returned when a string doesn't name a [`LogLevel`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLevelError {
+    input: String,
+}
+
+impl fmt::Display for ParseLevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown log level `{}`, expected one of trace, debug, info, warn, error or default",
+            self.input
+        )
+    }
+}
+
+impl Error for ParseLevelError {}
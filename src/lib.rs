@@ -1,162 +1,1741 @@
-use chrono::{DateTime, Local};
+use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
+use std::backtrace::Backtrace;
+use std::borrow::Cow;
+use std::collections::HashSet;
 use std::env;
-use std::fs::{create_dir_all, File};
 use std::io::Write;
-use std::path::PathBuf;
+use std::mem;
+use std::ops::Range;
+use std::path::{self, Path, PathBuf};
+use std::process;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
-#[derive(Debug)]
-enum LogLevel {
-    Default,
-    Error,
-    Debug,
-    Info,
-}
+mod async_logger;
+mod clock;
+mod console;
+mod error;
+#[cfg(feature = "log")]
+mod facade;
+mod format;
+mod global;
+mod guard;
+mod level;
+mod limit;
+mod macros;
+mod message;
+mod panic;
+mod rotation;
+mod shared;
+mod sink;
+#[cfg(all(unix, feature = "syslog"))]
+mod syslog;
+mod tcp;
+#[cfg(feature = "webhook")]
+mod webhook;
+
+use format::{Formatter, Layout, LevelLabels, Origin, Redactor, SeverityMap};
+use limit::{RateLimit, Sample};
+use rotation::{LogFiles, OpenFiles, Pending};
 
-#[derive(Debug)]
+pub use async_logger::{AsyncLogger, Overflow};
+pub use clock::{Clock, SystemClock};
+pub use console::ConsoleSink;
+pub use error::LoggerError;
+pub use format::{redact_patterns, LineEnding, LogFormat, TimePrecision, TimeUnit, TimestampMode};
+pub use global::InitGlobalError;
+pub use guard::LoggerGuard;
+pub use level::{LevelCounts, LogLevel, ParseLevelError, STATIC_MAX_LEVEL};
+pub use message::IntoMessage;
+pub use rotation::Rotation;
+pub use shared::SharedLogger;
+pub use sink::{FileSink, MemorySink, NullSink, Sink};
+#[cfg(all(unix, feature = "syslog"))]
+pub use syslog::SyslogSink;
+pub use tcp::TcpSink;
+#[cfg(feature = "webhook")]
+pub use webhook::WebhookSink;
+
+#[derive(Debug, Clone)]
 pub struct LogEntry {
-    timestap: DateTime<Local>,
+    timestamp: DateTime<FixedOffset>,
     level: LogLevel,
-    message: String,
+    message: Cow<'static, str>,
+    fields: Vec<(String, String)>,
+    location: Option<(&'static str, u32)>,
+    thread: Option<String>,
+    tag: Option<String>,
+    backtrace: Option<String>,
+}
+
+impl LogEntry {
+    pub fn new<Tz: TimeZone>(
+        timestamp: DateTime<Tz>,
+        level: LogLevel,
+        message: impl IntoMessage,
+    ) -> Self {
+        LogEntry {
+            timestamp: timestamp.fixed_offset(),
+            level,
+            message: message.into_message(),
+            fields: Vec::new(),
+            location: None,
+            thread: None,
+            tag: None,
+            backtrace: None,
+        }
+    }
+
+    pub fn timestamp(&self) -> DateTime<FixedOffset> {
+        self.timestamp
+    }
+
+    pub fn level(&self) -> LogLevel {
+        self.level
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn fields(&self) -> &[(String, String)] {
+        &self.fields
+    }
+
+    /// Source file and line the entry was logged from, when logged through a macro.
+    pub fn location(&self) -> Option<(&'static str, u32)> {
+        self.location
+    }
+
+    /// Name (or id, for unnamed threads) of the thread that logged the entry,
+    /// when [`Logger::with_thread_info`] is enabled.
+    pub fn thread(&self) -> Option<&str> {
+        self.thread.as_deref()
+    }
+
+    /// Category the entry was logged under, e.g. `db` or `http`.
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
+    /// Backtrace captured by [`Logger::error_with_backtrace`].
+    pub fn backtrace(&self) -> Option<&str> {
+        self.backtrace.as_deref()
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tag = Some(tag.to_string());
+        self
+    }
+
+    /// Attaches structured `key=value` context to the entry.
+    pub fn with_fields(mut self, fields: &[(&str, &str)]) -> Self {
+        self.fields = fields
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        self
+    }
 }
 
 pub struct Logger {
     entries: Vec<LogEntry>,
     file_path: Option<PathBuf>,
+    level: Option<LogLevel>,
+    level_env_var: String,
+    timestamp_format: Option<String>,
+    precision: TimePrecision,
+    timestamp_mode: TimestampMode,
+    clock: Arc<dyn Clock>,
+    utc: bool,
+    thread_info: bool,
+    level_labels: Option<Arc<LevelLabels>>,
+    severity_map: Option<Arc<SeverityMap>>,
+    origin: Option<Arc<Origin>>,
+    formatter: Option<Arc<Formatter>>,
+    redactor: Option<Arc<Redactor>>,
+    format: LogFormat,
+    line_ending: LineEnding,
+    sanitize: bool,
+    sync: bool,
+    routes: Vec<(LogLevel, PathBuf)>,
+    fallbacks: Vec<PathBuf>,
+    fallback: Option<PathBuf>,
+    files: OpenFiles,
+    header: bool,
+    bom: bool,
+    session_header: bool,
+    started: DateTime<Local>,
+    max_file_size: Option<u64>,
+    max_backups: usize,
+    compress_backups: bool,
+    rotation: Rotation,
+    console: Option<ConsoleSink>,
+    /// Each with its own level, or `None` to follow the logger's.
+    sinks: Vec<(Option<LogLevel>, Box<dyn Sink>)>,
+    max_buffered: usize,
+    max_message_len: usize,
+    dedup: bool,
+    sorted_flush: bool,
+    batch_writes: bool,
+    flush_retries: usize,
+    retry_delay: Duration,
+    write_level: LogLevel,
+    partial_line: Vec<u8>,
+    tag_filter: Option<HashSet<String>>,
+    only_tagged: bool,
+    rate_limits: Vec<RateLimit>,
+    samples: Vec<Sample>,
+    totals: LevelCounts,
+    first_logged: Option<DateTime<FixedOffset>>,
+    last_error: Option<LoggerError>,
+    /// Lines the last failed flush didn't write, reported if dropped unflushed.
+    unwritten: usize,
     flushed: Arc<AtomicBool>,
+    disabled: bool,
+}
+
+/// Configures a [`Logger`] without going through environment variables.
+///
+/// ```
+/// use simple_logger::{LogLevel, Logger};
+///
+/// let mut logger = Logger::builder()
+///     .file_path(std::env::temp_dir().join("app.log"))
+///     .level(LogLevel::Info)
+///     .timestamp_format(String::from("%Y-%m-%dT%H:%M:%S"))
+///     .build();
+///
+/// logger.info("server started");
+/// ```
+pub struct LoggerBuilder {
+    logger: Logger,
+}
+
+const DEFAULT_TIMESTAMP_FORMAT: &str = TimePrecision::Seconds.timestamp_format();
+const DEFAULT_MAX_BACKUPS: usize = 5;
+const DEFAULT_LEVEL_ENV_VAR: &str = "LOG_LEVEL";
+/// Silences the diagnostics printed on stderr, when a dropped logger fails to
+/// flush or a flush switches to a fallback path.
+const QUIET_ENV_VAR: &str = "SIMPLE_LOGGER_QUIET";
+
+fn app_name() -> String {
+    env::current_exe()
+        .ok()
+        .and_then(|exe| {
+            exe.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| String::from("app"))
+}
+
+fn is_directory(path: &Path) -> bool {
+    path.is_dir()
+        || path
+            .as_os_str()
+            .to_string_lossy()
+            .ends_with(path::is_separator)
+}
+
+#[cfg(target_os = "windows")]
+pub fn default_log_path() -> PathBuf {
+    env::var_os("PROGRAMDATA")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(r"C:\ProgramData"))
+        .join("Logs")
+        .join(format!("{}.log", app_name()))
+}
+
+#[cfg(target_os = "macos")]
+pub fn default_log_path() -> PathBuf {
+    PathBuf::from("/Library/Logs").join(format!("{}.log", app_name()))
 }
 
-fn default_log_path() -> PathBuf {
-    if cfg!(target_os = "windows") {
-        PathBuf::from(r"C:\PorgramData").join("Logs")
-    } else if cfg!(target_os = "macos") {
-        PathBuf::from("/Library/Logs")
-    } else {
-        PathBuf::from("/var/log")
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub fn default_log_path() -> PathBuf {
+    PathBuf::from("/var/log").join(format!("{}.log", app_name()))
+}
+
+fn thread_name() -> String {
+    let thread = thread::current();
+    match thread.name() {
+        Some(name) => name.to_string(),
+        None => format!("{:?}", thread.id()),
     }
 }
 
-fn get_log_level() -> LogLevel {
-    match env::var("LOG_LEVEL").unwrap().as_str() {
-        "debug" => LogLevel::Debug,
-        "info" => LogLevel::Info,
-        _ => LogLevel::Default,
+/// Shortens `message` to at most `max` bytes, backing off to a char boundary.
+fn truncate(message: Cow<'static, str>, max: usize) -> Cow<'static, str> {
+    if max == 0 || message.len() <= max {
+        return message;
+    }
+    let mut end = max;
+    while !message.is_char_boundary(end) {
+        end -= 1;
+    }
+    Cow::Owned(format!("{}…[truncated]", &message[..end]))
+}
+
+fn get_log_level(var: &str) -> LogLevel {
+    env::var(var)
+        .ok()
+        .and_then(|level| level.parse().ok())
+        .unwrap_or_default()
+}
+
+impl LoggerBuilder {
+    /// Defaults to [`default_log_path`].
+    pub fn file_path(mut self, file_path: PathBuf) -> Self {
+        self.logger.file_path = Some(file_path);
+        self
+    }
+
+    /// Defaults to [`LogLevel::Default`], which writes every entry.
+    pub fn level(mut self, level: LogLevel) -> Self {
+        self.logger.level = Some(level);
+        self
+    }
+
+    /// See [`Logger::with_timestamp_format`].
+    pub fn timestamp_format(mut self, timestamp_format: String) -> Self {
+        self.logger.timestamp_format = Some(timestamp_format);
+        self
+    }
+
+    /// Rotates the log file before a write would grow it past `bytes`.
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.logger.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Number of rotated files (`app.log.1`, `app.log.2`, ...) to keep. Defaults to 5.
+    pub fn max_backups(mut self, count: usize) -> Self {
+        self.logger.max_backups = count;
+        self
+    }
+
+    pub fn rotation(mut self, rotation: Rotation) -> Self {
+        self.logger.rotation = rotation;
+        self
+    }
+
+    /// Gzips backups as they're rotated out, e.g. to `app.log.1.gz`. The
+    /// active file is never compressed.
+    #[cfg(feature = "compression")]
+    pub fn compress_backups(mut self, compress: bool) -> Self {
+        self.logger.compress_backups = compress;
+        self
+    }
+
+    pub fn build(self) -> Logger {
+        self.logger
     }
 }
 
 impl Logger {
+    /// Creates a logger writing to `file_path`, with its level read from `LOG_LEVEL` at flush time.
     pub fn new(file_path: PathBuf) -> Self {
+        Logger::with_file_path(Some(file_path))
+    }
+
+    /// Like [`new`](Self::new), with room for `capacity` entries before the
+    /// buffer has to grow.
+    pub fn with_capacity(file_path: PathBuf, capacity: usize) -> Self {
+        let mut logger = Logger::new(file_path);
+        logger.entries.reserve_exact(capacity);
+        logger
+    }
+
+    fn with_file_path(file_path: Option<PathBuf>) -> Self {
         Logger {
             entries: Vec::new(),
-            file_path: Some(file_path),
-            flushed: Arc::new(AtomicBool::new(false)),
+            file_path,
+            level: None,
+            level_env_var: String::from(DEFAULT_LEVEL_ENV_VAR),
+            timestamp_format: None,
+            precision: TimePrecision::default(),
+            timestamp_mode: TimestampMode::default(),
+            clock: Arc::new(SystemClock),
+            utc: false,
+            thread_info: false,
+            level_labels: None,
+            severity_map: None,
+            origin: None,
+            formatter: None,
+            redactor: None,
+            format: LogFormat::default(),
+            line_ending: LineEnding::default(),
+            sanitize: false,
+            sync: false,
+            routes: Vec::new(),
+            fallbacks: Vec::new(),
+            fallback: None,
+            files: OpenFiles::default(),
+            header: false,
+            bom: false,
+            session_header: false,
+            started: Local::now(),
+            max_file_size: None,
+            max_backups: DEFAULT_MAX_BACKUPS,
+            compress_backups: false,
+            rotation: Rotation::default(),
+            console: None,
+            sinks: Vec::new(),
+            max_buffered: 0,
+            max_message_len: 0,
+            dedup: false,
+            sorted_flush: false,
+            batch_writes: true,
+            flush_retries: 0,
+            retry_delay: Duration::ZERO,
+            write_level: LogLevel::Info,
+            partial_line: Vec::new(),
+            tag_filter: None,
+            only_tagged: false,
+            rate_limits: Vec::new(),
+            samples: Vec::new(),
+            totals: LevelCounts::default(),
+            first_logged: None,
+            last_error: None,
+            unwritten: 0,
+            flushed: Arc::new(AtomicBool::new(true)),
+            disabled: false,
         }
     }
 
-    pub fn debug(&mut self, message: &str) {
-        self.entries.push(LogEntry {
-            timestap: Local::now(),
-            level: LogLevel::Debug,
-            message: message.to_string(),
-        });
+    /// A new logger with the same settings, but nothing buffered, counted or
+    /// open yet. The console and sinks can't be copied, so it has none; add
+    /// them to the copy as needed. Combine with
+    /// [`set_file_path`](Self::set_file_path) to log to several files alike.
+    pub fn clone_config(&self) -> Logger {
+        Logger {
+            entries: Vec::new(),
+            file_path: self.file_path.clone(),
+            level: self.level,
+            level_env_var: self.level_env_var.clone(),
+            timestamp_format: self.timestamp_format.clone(),
+            precision: self.precision,
+            timestamp_mode: self.timestamp_mode,
+            clock: Arc::clone(&self.clock),
+            utc: self.utc,
+            thread_info: self.thread_info,
+            level_labels: self.level_labels.clone(),
+            severity_map: self.severity_map.clone(),
+            origin: self.origin.clone(),
+            formatter: self.formatter.clone(),
+            redactor: self.redactor.clone(),
+            format: self.format,
+            line_ending: self.line_ending,
+            sanitize: self.sanitize,
+            sync: self.sync,
+            routes: self.routes.clone(),
+            fallbacks: self.fallbacks.clone(),
+            fallback: None,
+            files: OpenFiles::default(),
+            header: self.header,
+            bom: self.bom,
+            session_header: self.session_header,
+            started: self.clock.now().with_timezone(&Local),
+            max_file_size: self.max_file_size,
+            max_backups: self.max_backups,
+            compress_backups: self.compress_backups,
+            rotation: self.rotation,
+            console: None,
+            sinks: Vec::new(),
+            max_buffered: self.max_buffered,
+            max_message_len: self.max_message_len,
+            dedup: self.dedup,
+            sorted_flush: self.sorted_flush,
+            batch_writes: self.batch_writes,
+            flush_retries: self.flush_retries,
+            retry_delay: self.retry_delay,
+            write_level: self.write_level,
+            partial_line: Vec::new(),
+            tag_filter: self.tag_filter.clone(),
+            only_tagged: self.only_tagged,
+            rate_limits: self.rate_limits.iter().map(RateLimit::reset).collect(),
+            samples: self.samples.iter().map(Sample::reset).collect(),
+            totals: LevelCounts::default(),
+            first_logged: None,
+            last_error: None,
+            unwritten: 0,
+            flushed: Arc::new(AtomicBool::new(true)),
+            disabled: self.disabled,
+        }
     }
 
-    pub fn info(&mut self, message: &str) {
-        self.entries.push(LogEntry {
-            timestap: Local::now(),
-            level: LogLevel::Info,
-            message: message.to_string(),
-        });
+    /// Writes to `file_path` from the next flush on.
+    pub fn set_file_path(&mut self, file_path: PathBuf) {
+        self.file_path = Some(file_path);
+        self.files = OpenFiles::default();
+        self.fallback = None;
     }
 
-    pub fn error(&mut self, message: &str) {
-        self.entries.push(LogEntry {
-            timestap: Local::now(),
-            level: LogLevel::Error,
-            message: message.to_string(),
-        });
+    /// A logger that drops every entry as it's logged and never touches the
+    /// log file, for turning logging off without changing the call sites.
+    /// Its only sink is a [`NullSink`].
+    pub fn disabled() -> Self {
+        let mut logger = Logger::builder().build();
+        logger.disabled = true;
+        logger.add_sink(NullSink);
+        logger
     }
 
-    pub fn flush(self: Self) -> std::io::Result<()> {
-        if self.flushed.load(Ordering::Relaxed) {
-            return Ok(());
-        }
+    /// Creates a logger writing to `LOG_FILE` at the level in `LOG_LEVEL`, falling
+    /// back to [`default_log_path`] and [`LogLevel::Default`] when they're unset.
+    pub fn from_env() -> Self {
+        let file_path = env::var_os("LOG_FILE")
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from);
+        let mut logger = Logger::with_file_path(file_path);
+        logger.level = Some(get_log_level(DEFAULT_LEVEL_ENV_VAR));
+        logger
+    }
 
-        let log_level = get_log_level();
-        let path = match self.file_path {
-            Some(path) => path,
+    /// The file [`flush`](Self::flush) writes to. A configured path that is a
+    /// directory, or ends with a separator, gets `<app>.log` appended.
+    pub fn file_path(&self) -> PathBuf {
+        match &self.file_path {
+            Some(path) if is_directory(path) => path.join(format!("{}.log", app_name())),
+            Some(path) => path.clone(),
             None => default_log_path(),
+        }
+    }
+
+    pub fn builder() -> LoggerBuilder {
+        let mut logger = Logger::with_file_path(None);
+        logger.level = Some(LogLevel::Default);
+        LoggerBuilder { logger }
+    }
+
+    /// Sets the [chrono format string](chrono::format::strftime) used for text
+    /// timestamps. Defaults to `%Y-%m-%d %H:%M:%S`; the string isn't validated.
+    pub fn with_timestamp_format(mut self, timestamp_format: String) -> Self {
+        self.timestamp_format = Some(timestamp_format);
+        self
+    }
+
+    /// Adds fractional seconds to the default timestamp format. Has no effect
+    /// once a custom format is set with [`with_timestamp_format`](Self::with_timestamp_format).
+    pub fn with_time_precision(mut self, precision: TimePrecision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Writes timestamps as numbers since the Unix epoch instead, with
+    /// [`TimestampMode::Epoch`], for tools that sort and subtract them, or as
+    /// the time since the logger was created with [`TimestampMode::Relative`].
+    pub fn with_timestamp_mode(mut self, timestamp_mode: TimestampMode) -> Self {
+        self.timestamp_mode = timestamp_mode;
+        self
+    }
+
+    /// Timestamps entries in UTC instead of local time. The default text
+    /// timestamp then ends in `Z`.
+    pub fn with_utc(mut self, utc: bool) -> Self {
+        self.utc = utc;
+        self
+    }
+
+    /// Records the name (or id) of the logging thread on each entry, written as
+    /// `[ts] [worker-3] INFO: ...`.
+    pub fn with_thread_info(mut self, thread_info: bool) -> Self {
+        self.thread_info = thread_info;
+        self
+    }
+
+    /// Replaces the level label written for each entry, e.g. `ERR` instead of `ERROR`.
+    ///
+    /// ```
+    /// use simple_logger::{LogLevel, Logger};
+    ///
+    /// let logger = Logger::builder().build().with_level_labels(|level| match level {
+    ///     LogLevel::Error => "ERR",
+    ///     LogLevel::Warn => "WRN",
+    ///     _ => "INF",
+    /// });
+    /// ```
+    pub fn with_level_labels<F>(mut self, labels: F) -> Self
+    where
+        F: Fn(&LogLevel) -> &str + Send + Sync + 'static,
+    {
+        self.level_labels = Some(Arc::new(labels));
+        self
+    }
+
+    /// Takes entry timestamps from `clock` instead of the system time, e.g. to
+    /// get predictable output in tests.
+    pub fn with_clock(mut self, clock: Box<dyn Clock>) -> Self {
+        self.clock = Arc::from(clock);
+        // Relative timestamps count from the logger's creation on the same clock.
+        self.started = self.clock.now().with_timezone(&Local);
+        self
+    }
+
+    /// Includes the hostname and process id in every line, as `[host:pid]`
+    /// in text and `hostname`/`pid` fields in JSON and logfmt. Both are looked
+    /// up once, here.
+    pub fn with_origin(mut self, origin: bool) -> Self {
+        self.origin = origin.then(|| Arc::new(Origin::current()));
+        self
+    }
+
+    /// Rewrites each message with `redactor` as it's logged, e.g. with
+    /// [`redact_patterns`], so neither the log file nor any sink sees the
+    /// original.
+    pub fn with_redactor<F>(mut self, redactor: F) -> Self
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        self.redactor = Some(Arc::new(redactor));
+        self
+    }
+
+    /// Numbers the `severity` field of JSON and logfmt output with `map`, to
+    /// match the ingestion schema. Defaults to [`LogLevel::syslog_severity`].
+    pub fn with_severity_map<F>(mut self, map: F) -> Self
+    where
+        F: Fn(&LogLevel) -> u8 + Send + Sync + 'static,
+    {
+        self.severity_map = Some(Arc::new(map));
+        self
+    }
+
+    /// Renders each line of the log file with `formatter`, overriding the
+    /// [`LogFormat`] and the other layout settings.
+    pub fn with_formatter<F>(mut self, formatter: F) -> Self
+    where
+        F: Fn(&LogEntry) -> String + Send + Sync + 'static,
+    {
+        self.formatter = Some(Arc::new(formatter));
+        self
+    }
+
+    pub fn with_format(mut self, format: LogFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Writes entries at each listed level to its own file instead of the
+    /// logger's path. Several levels may share a file; unlisted levels keep
+    /// going to the logger's path. Rotation applies to every file.
+    ///
+    /// ```
+    /// use simple_logger::{LogLevel, Logger};
+    ///
+    /// let dir = std::env::temp_dir();
+    /// let logger = Logger::new(dir.join("app.log")).with_level_routing([
+    ///     (LogLevel::Warn, dir.join("app.error.log")),
+    ///     (LogLevel::Error, dir.join("app.error.log")),
+    /// ]);
+    /// ```
+    pub fn with_level_routing<I>(mut self, routes: I) -> Self
+    where
+        I: IntoIterator<Item = (LogLevel, PathBuf)>,
+    {
+        self.routes = routes.into_iter().collect();
+        self
+    }
+
+    /// Makes [`flush`](Self::flush) wait until the written entries are on disk,
+    /// so they survive a crash or power loss. This costs a disk round trip per
+    /// flush, which can take milliseconds, so it's off by default.
+    pub fn with_sync(mut self, sync: bool) -> Self {
+        self.sync = sync;
+        self
+    }
+
+    /// Escapes control characters in messages written to the log file, e.g.
+    /// an ANSI escape becomes `\x1b` and a newline `\n`, so a message can't
+    /// restyle a terminal or fake extra entries. The console always does this
+    /// unless turned off with [`ConsoleSink::with_sanitize`].
+    pub fn with_sanitize(mut self, sanitize: bool) -> Self {
+        self.sanitize = sanitize;
+        self
+    }
+
+    /// Ends each line of the log file with `line_ending` instead of `\n`.
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Starts each new [`LogFormat::Ndjson`] file with a metadata line. Files
+    /// that already exist are appended to without one.
+    pub fn with_header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Starts each new log file with a UTF-8 byte order mark, for Windows
+    /// tools that misdetect the encoding without one. Files that already exist
+    /// are appended to without one.
+    pub fn with_bom(mut self, bom: bool) -> Self {
+        self.bom = bom;
+        self
+    }
+
+    /// Starts each new log file with a `# session started ...` line holding the
+    /// logger's start time, process id, OS and crate version. Files that
+    /// already exist are appended to without one.
+    pub fn with_session_header(mut self, session_header: bool) -> Self {
+        self.session_header = session_header;
+        self
+    }
+
+    /// Echoes every entry to stderr as it's logged, colored by level, when
+    /// `color` is true. `false` only turns colors off for an existing console.
+    /// Colors never reach the log file.
+    pub fn with_color(mut self, color: bool) -> Self {
+        match &mut self.console {
+            Some(console) => console.set_color(color),
+            None if color => self.console = Some(ConsoleSink::stderr().with_color(true)),
+            None => {}
+        }
+        self
+    }
+
+    /// Echoes every entry to the console as it's logged, sending entries at or
+    /// above `level` to stderr and the rest to stdout.
+    pub fn with_stderr_threshold(mut self, level: LogLevel) -> Self {
+        self.console
+            .get_or_insert_with(ConsoleSink::split)
+            .set_stderr_threshold(level);
+        self
+    }
+
+    /// Echoes every entry to `console` as it's logged.
+    pub fn with_console(mut self, console: ConsoleSink) -> Self {
+        self.console = Some(console);
+        self
+    }
+
+    /// Flushes automatically once `max` entries are buffered. `0` (the
+    /// default) never flushes automatically; see [`take_last_error`](Self::take_last_error)
+    /// for failures.
+    pub fn with_max_buffered(mut self, max: usize) -> Self {
+        self.max_buffered = max;
+        self
+    }
+
+    /// Cuts messages longer than `max` bytes down to size, marking them with
+    /// `…[truncated]`. `0` (the default) keeps messages whole. Messages are
+    /// cut after the [redactor](Self::with_redactor) has run.
+    pub fn with_max_message_len(mut self, max: usize) -> Self {
+        self.max_message_len = max;
+        self
+    }
+
+    /// Only writes tagged entries whose tag is in `tags` when flushing.
+    /// Untagged entries are still written, unless [`with_only_tagged`](Self::with_only_tagged) is set.
+    pub fn with_tag_filter(mut self, tags: HashSet<String>) -> Self {
+        self.tag_filter = Some(tags);
+        self
+    }
+
+    /// Drops untagged entries when flushing.
+    pub fn with_only_tagged(mut self, only_tagged: bool) -> Self {
+        self.only_tagged = only_tagged;
+        self
+    }
+
+    /// Sets the level of entries created by writing to the logger through
+    /// [`io::Write`](std::io::Write). Defaults to [`LogLevel::Info`].
+    pub fn with_write_level(mut self, level: LogLevel) -> Self {
+        self.write_level = level;
+        self
+    }
+
+    /// Writes all the lines of a flush with one call per file, which is the
+    /// default. With `false`, each line is written as soon as it's rendered.
+    pub fn with_batched_writes(mut self, batch: bool) -> Self {
+        self.batch_writes = batch;
+        self
+    }
+
+    /// Retries a failed write up to `retries` times, sleeping `delay` before
+    /// each attempt, before [`flush`](Self::flush) gives up and returns the
+    /// error. Lines that were already written aren't written again.
+    pub fn with_flush_retries(mut self, retries: usize, delay: Duration) -> Self {
+        self.flush_retries = retries;
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Sorts the buffered entries by timestamp when flushing, see
+    /// [`sort_by_time`](Self::sort_by_time).
+    pub fn with_sorted_flush(mut self, sorted_flush: bool) -> Self {
+        self.sorted_flush = sorted_flush;
+        self
+    }
+
+    /// Collapses runs of consecutive entries with the same level and message
+    /// into the first one when flushing, suffixed with `(repeated N times)`
+    /// (or a `repeated` field in JSON and logfmt), where `N` counts the whole run.
+    pub fn with_dedup(mut self, dedup: bool) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// Keeps at most `max` entries at `level` per second, going by entry
+    /// timestamps. Dropped entries are reported by a single summary entry once
+    /// the second is over, or on the next flush.
+    pub fn with_rate_limit(mut self, level: LogLevel, max: u32) -> Self {
+        self.rate_limits.retain(|limit| limit.level != level);
+        self.rate_limits.push(RateLimit::new(level, max));
+        self
+    }
+
+    /// Where to write when the log file can't be opened, tried in order.
+    /// Directories get the log file's name appended. The first one that works
+    /// is used from then on, until [`reopen`](Self::reopen), and a warning
+    /// entry noting the switch is written there and printed on stderr,
+    /// whatever the level. By default there are none, and [`flush`](Self::flush)
+    /// reports the error instead. Fallback files are created with the default
+    /// permissions, so avoid directories other users can write to, like the
+    /// shared temp dir.
+    pub fn with_fallback_paths(mut self, paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        self.fallbacks = paths.into_iter().collect();
+        self
+    }
+
+    /// Keeps only one in every `every` entries at `level`, starting with the
+    /// first, and drops the rest as they're logged. Errors are never sampled.
+    pub fn with_sampling(mut self, level: LogLevel, every: u32) -> Self {
+        self.samples.retain(|sample| sample.level != level);
+        if level != LogLevel::Error {
+            self.samples.push(Sample::new(level, every));
+        }
+        self
+    }
+
+    /// Sends every entry logged from now on that's at or above the logger's
+    /// level to `sink` as well.
+    pub fn add_sink(&mut self, sink: impl Sink + 'static) {
+        self.sinks.push((None, Box::new(sink)));
+    }
+
+    /// Like [`add_sink`](Self::add_sink), but sends `sink` the entries at or
+    /// above `level` instead, whatever the logger's level, e.g. to keep the
+    /// console quieter than the file or a debug file more verbose.
+    pub fn add_sink_with_level(&mut self, level: LogLevel, sink: impl Sink + 'static) {
+        self.sinks.push((Some(level), Box::new(sink)));
+    }
+
+    /// Captures every entry logged from now on in `sink`.
+    ///
+    /// ```
+    /// use simple_logger::{Logger, MemorySink};
+    ///
+    /// let sink = MemorySink::new();
+    /// let mut logger = Logger::builder().build().with_memory_sink(sink.clone());
+    /// logger.info("user signed in");
+    /// logger.warn("password expires soon");
+    ///
+    /// let lines = sink.lines();
+    /// assert_eq!(lines.len(), 2);
+    /// assert!(lines[0].ends_with("INFO: user signed in"));
+    /// assert!(lines[1].ends_with("WARN: password expires soon"));
+    /// # logger.clear();
+    /// ```
+    pub fn with_memory_sink(mut self, sink: MemorySink) -> Self {
+        self.add_sink(sink);
+        self
+    }
+
+    /// Reads the threshold from `var` instead of `LOG_LEVEL`, for loggers
+    /// created without an explicit level.
+    pub fn with_level_env_var(mut self, var: String) -> Self {
+        self.level_env_var = var;
+        self
+    }
+
+    /// Sets the level explicitly, so the level variable is no longer read.
+    pub fn set_level(&mut self, level: LogLevel) {
+        self.level = Some(level);
+    }
+
+    /// Reads the level variable again and keeps its level, e.g. from a signal
+    /// handler so a running service can change verbosity. A missing or
+    /// unparsable value leaves the level alone. Returns whether it changed.
+    pub fn reload_level(&mut self) -> bool {
+        let Some(level) = env::var(&self.level_env_var)
+            .ok()
+            .and_then(|level| level.parse().ok())
+        else {
+            return false;
         };
+        let changed = level != self.log_level();
+        self.level = Some(level);
+        changed
+    }
+
+    /// The explicit level, or the one currently set in the level variable.
+    fn log_level(&self) -> LogLevel {
+        self.level
+            .unwrap_or_else(|| get_log_level(&self.level_env_var))
+    }
+
+    /// Whether an entry at `level` would currently make it to the log file,
+    /// or to a sink added with a lower level of its own.
+    pub fn enabled(&self, level: LogLevel) -> bool {
+        self.records(level)
+            && (level >= self.log_level()
+                || self
+                    .sinks
+                    .iter()
+                    .any(|(own, _)| own.is_some_and(|own| level >= own)))
+    }
+
+    /// Whether entries at `level` are buffered at all.
+    fn records(&self, level: LogLevel) -> bool {
+        level >= STATIC_MAX_LEVEL && !self.disabled
+    }
+
+    fn now(&self) -> DateTime<FixedOffset> {
+        let now = self.clock.now();
+        if self.utc {
+            now.with_timezone(&Utc).fixed_offset()
+        } else {
+            now
+        }
+    }
+
+    /// Builds an entry stamped with everything captured at log time.
+    fn entry(&self, level: LogLevel, message: impl IntoMessage) -> LogEntry {
+        let mut entry = LogEntry::new(self.now(), level, message.into_message());
+        if self.thread_info {
+            entry.thread = Some(thread_name());
+        }
+        entry
+    }
+
+    /// Applies the log-time settings to an entry built on another thread by
+    /// [`AsyncLogger`], then buffers it.
+    fn adopt(&mut self, mut entry: LogEntry) {
+        if self.utc {
+            entry.timestamp = entry.timestamp.with_timezone(&Utc).fixed_offset();
+        }
+        self.push(entry);
+    }
+
+    /// Logs `message` at `level`, for when the level is only known at runtime.
+    pub fn log(&mut self, level: LogLevel, message: impl IntoMessage) {
+        if !self.records(level) {
+            return;
+        }
+        self.push(self.entry(level, message));
+    }
+
+    #[doc(hidden)]
+    pub fn __record_at(
+        &mut self,
+        level: LogLevel,
+        message: impl IntoMessage,
+        file: &'static str,
+        line: u32,
+    ) {
+        if !self.records(level) {
+            return;
+        }
+        let mut entry = self.entry(level, message);
+        entry.location = Some((file, line));
+        self.push(entry);
+    }
+
+    fn record_with(&mut self, level: LogLevel, message: impl IntoMessage, fields: &[(&str, &str)]) {
+        if !self.records(level) {
+            return;
+        }
+        self.push(self.entry(level, message).with_fields(fields));
+    }
+
+    fn record_tagged(&mut self, level: LogLevel, tag: &str, message: impl IntoMessage) {
+        if !self.records(level) {
+            return;
+        }
+        self.push(self.entry(level, message).with_tag(tag));
+    }
+
+    /// Buffers an already built entry, keeping its timestamp.
+    pub fn push(&mut self, entry: LogEntry) {
+        if !self.records(entry.level) {
+            return;
+        }
+        if let Some(sample) = self.samples.iter_mut().find(|s| s.level == entry.level) {
+            if !sample.admit() {
+                return;
+            }
+        }
+        if let Some(limit) = self.rate_limits.iter_mut().find(|l| l.level == entry.level) {
+            let (keep, summary) = limit.admit(&entry);
+            if let Some(summary) = summary {
+                self.buffer(summary);
+            }
+            if !keep {
+                return;
+            }
+        }
+        self.buffer(entry);
+
+        if self.max_buffered > 0 && self.entries.len() >= self.max_buffered {
+            if let Err(err) = self.flush() {
+                self.last_error = Some(err);
+            }
+        }
+    }
+
+    /// Fans `entry` out to the sinks and adds it to the buffer.
+    fn buffer(&mut self, mut entry: LogEntry) {
+        self.redact(&mut entry);
+        // Only after redacting, so a cut can't leave part of a secret behind.
+        entry.message = truncate(mem::take(&mut entry.message), self.max_message_len);
+        let has_sinks = self.console.is_some() || !self.sinks.is_empty();
+        if has_sinks && self.records(entry.level) {
+            let log_level = self.log_level();
+            // Sink failures don't stop the entry from reaching the log file.
+            if let Some(console) = &mut self.console {
+                if entry.level >= log_level {
+                    if let Err(err) = console.write_entry(&entry) {
+                        self.last_error = Some(LoggerError::Sink(err));
+                    }
+                }
+            }
+            for (own, sink) in &mut self.sinks {
+                if entry.level < own.unwrap_or(log_level) {
+                    continue;
+                }
+                if let Err(err) = sink.write_entry(&entry) {
+                    self.last_error = Some(LoggerError::Sink(err));
+                }
+            }
+        }
+        self.totals.add(entry.level);
+        self.first_logged.get_or_insert(entry.timestamp);
+        self.entries.push(entry);
+        self.flushed.store(false, Ordering::Relaxed);
+    }
+
+    /// Logs whatever was written through [`io::Write`](std::io::Write) since
+    /// the last complete line.
+    fn log_partial_line(&mut self) {
+        if self.partial_line.is_empty() {
+            return;
+        }
+        let line = String::from_utf8_lossy(&mem::take(&mut self.partial_line)).into_owned();
+        let line = line.strip_suffix('\r').unwrap_or(&line);
+        if !line.is_empty() {
+            self.log(self.write_level, line.to_string());
+        }
+    }
+
+    fn redact(&self, entry: &mut LogEntry) {
+        if let Some(redact) = &self.redactor {
+            entry.message = redact(&entry.message).into();
+        }
+    }
+
+    /// Counts the buffered entries by level.
+    pub fn stats(&self) -> LevelCounts {
+        let mut counts = LevelCounts::default();
+        for entry in &self.entries {
+            counts.add(entry.level);
+        }
+        counts
+    }
+
+    /// Counts every entry logged so far by level, including ones already
+    /// flushed or cleared.
+    pub fn lifetime_stats(&self) -> LevelCounts {
+        self.totals
+    }
+
+    /// Drops every buffered entry without writing it.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.flushed.store(true, Ordering::Relaxed);
+    }
+
+    /// Takes every buffered entry out of the logger without writing it, leaving
+    /// the buffer empty.
+    pub fn drain(&mut self) -> Vec<LogEntry> {
+        self.flushed.store(true, Ordering::Relaxed);
+        mem::take(&mut self.entries)
+    }
+
+    /// Moves every entry buffered in `other` into this logger, leaving `other`
+    /// empty, and sorts the result with [`sort_by_time`](Self::sort_by_time).
+    pub fn append(&mut self, other: &mut Logger) {
+        self.extend(other.drain());
+        self.sort_by_time();
+    }
+
+    /// Orders the buffered entries by timestamp. The sort is stable, so entries
+    /// logged in the same instant keep their order.
+    pub fn sort_by_time(&mut self) {
+        self.entries.sort_by_key(|entry| entry.timestamp);
+    }
+
+    /// Iterates over the buffered entries, oldest first.
+    ///
+    /// ```
+    /// use simple_logger::{LogLevel, Logger};
+    ///
+    /// let mut logger = Logger::builder().build();
+    /// logger.info("connected");
+    /// logger.error("connection lost");
+    ///
+    /// let errors: Vec<&str> = logger
+    ///     .iter()
+    ///     .filter(|entry| matches!(entry.level(), LogLevel::Error))
+    ///     .map(|entry| entry.message())
+    ///     .collect();
+    /// assert_eq!(errors, ["connection lost"]);
+    /// # logger.clear();
+    /// ```
+    pub fn iter(&self) -> std::slice::Iter<'_, LogEntry> {
+        self.entries.iter()
+    }
+
+    /// The most recently buffered entry, if any.
+    pub fn last(&self) -> Option<&LogEntry> {
+        self.entries.last()
+    }
+
+    /// Keeps only the buffered entries for which `keep` returns true, like
+    /// [`Vec::retain`]. Useful for redaction or sampling right before a flush.
+    pub fn retain<F: FnMut(&LogEntry) -> bool>(&mut self, keep: F) {
+        self.entries.retain(keep);
+    }
+
+    /// Number of buffered entries waiting for a flush.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether every entry logged so far has been flushed (or cleared), so
+    /// dropping the logger won't flush again.
+    pub fn is_flushed(&self) -> bool {
+        self.flushed.load(Ordering::Relaxed)
+    }
+
+    /// Number of entries the buffer can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Takes the most recent error from a sink write or an automatic flush,
+    /// neither of which can be reported by the logging call itself.
+    pub fn take_last_error(&mut self) -> Option<LoggerError> {
+        self.last_error.take()
+    }
+
+    pub fn trace(&mut self, message: impl IntoMessage) {
+        self.log(LogLevel::Trace, message);
+    }
+
+    pub fn trace_with(&mut self, message: impl IntoMessage, fields: &[(&str, &str)]) {
+        self.record_with(LogLevel::Trace, message, fields);
+    }
 
-        let mut file = if path.exists() {
-            File::open(&path)?
+    pub fn trace_tagged(&mut self, tag: &str, message: impl IntoMessage) {
+        self.record_tagged(LogLevel::Trace, tag, message);
+    }
+
+    pub fn debug(&mut self, message: impl IntoMessage) {
+        self.log(LogLevel::Debug, message);
+    }
+
+    pub fn debug_with(&mut self, message: impl IntoMessage, fields: &[(&str, &str)]) {
+        self.record_with(LogLevel::Debug, message, fields);
+    }
+
+    pub fn debug_tagged(&mut self, tag: &str, message: impl IntoMessage) {
+        self.record_tagged(LogLevel::Debug, tag, message);
+    }
+
+    pub fn info(&mut self, message: impl IntoMessage) {
+        self.log(LogLevel::Info, message);
+    }
+
+    pub fn info_with(&mut self, message: impl IntoMessage, fields: &[(&str, &str)]) {
+        self.record_with(LogLevel::Info, message, fields);
+    }
+
+    pub fn info_tagged(&mut self, tag: &str, message: impl IntoMessage) {
+        self.record_tagged(LogLevel::Info, tag, message);
+    }
+
+    pub fn warn(&mut self, message: impl IntoMessage) {
+        self.log(LogLevel::Warn, message);
+    }
+
+    pub fn warn_with(&mut self, message: impl IntoMessage, fields: &[(&str, &str)]) {
+        self.record_with(LogLevel::Warn, message, fields);
+    }
+
+    pub fn warn_tagged(&mut self, tag: &str, message: impl IntoMessage) {
+        self.record_tagged(LogLevel::Warn, tag, message);
+    }
+
+    pub fn error(&mut self, message: impl IntoMessage) {
+        self.log(LogLevel::Error, message);
+    }
+
+    pub fn error_with(&mut self, message: impl IntoMessage, fields: &[(&str, &str)]) {
+        self.record_with(LogLevel::Error, message, fields);
+    }
+
+    pub fn error_tagged(&mut self, tag: &str, message: impl IntoMessage) {
+        self.record_tagged(LogLevel::Error, tag, message);
+    }
+
+    /// Logs `message` at `level` only when `cond` holds.
+    pub fn log_if(&mut self, cond: bool, level: LogLevel, message: impl IntoMessage) {
+        if cond {
+            self.log(level, message);
+        }
+    }
+
+    /// Like [`log_if`](Self::log_if), but only builds the message when it
+    /// would be logged: `cond` holds and `level` is enabled.
+    pub fn log_if_with<F, M>(&mut self, cond: bool, level: LogLevel, message: F)
+    where
+        F: FnOnce() -> M,
+        M: IntoMessage,
+    {
+        if cond && self.enabled(level) {
+            self.log(level, message());
+        }
+    }
+
+    pub fn trace_if(&mut self, cond: bool, message: impl IntoMessage) {
+        self.log_if(cond, LogLevel::Trace, message);
+    }
+
+    pub fn debug_if(&mut self, cond: bool, message: impl IntoMessage) {
+        self.log_if(cond, LogLevel::Debug, message);
+    }
+
+    pub fn info_if(&mut self, cond: bool, message: impl IntoMessage) {
+        self.log_if(cond, LogLevel::Info, message);
+    }
+
+    pub fn warn_if(&mut self, cond: bool, message: impl IntoMessage) {
+        self.log_if(cond, LogLevel::Warn, message);
+    }
+
+    /// Logs an error together with a backtrace of the calling thread, which
+    /// text output writes as indented lines below the entry.
+    pub fn error_with_backtrace(&mut self, message: impl IntoMessage) {
+        if !self.records(LogLevel::Error) {
+            return;
+        }
+        let mut entry = self.entry(LogLevel::Error, message);
+        entry.backtrace = Some(Backtrace::force_capture().to_string());
+        self.push(entry);
+    }
+
+    pub fn error_if(&mut self, cond: bool, message: impl IntoMessage) {
+        self.log_if(cond, LogLevel::Error, message);
+    }
+
+    fn timestamp_format(&self) -> Cow<'_, str> {
+        match &self.timestamp_format {
+            Some(timestamp_format) => Cow::Borrowed(timestamp_format),
+            None if self.utc => Cow::Owned(format!("{}Z", self.precision.timestamp_format())),
+            None => Cow::Borrowed(self.precision.timestamp_format()),
+        }
+    }
+
+    fn render(&self, entry: &LogEntry) -> String {
+        let message = if self.sanitize {
+            format::sanitize(&entry.message)
         } else {
-            if let Some(parent) = path.parent() {
-                create_dir_all(parent)?;
+            None
+        };
+        let cleaned;
+        let entry = match message {
+            Some(message) => {
+                cleaned = LogEntry {
+                    message: message.into(),
+                    ..entry.clone()
+                };
+                &cleaned
             }
-            println!("Creating new log file at: {:?}", path);
-            File::create(&path)?
+            None => entry,
         };
+        if let Some(formatter) = &self.formatter {
+            return formatter(entry);
+        }
+        let timestamp_format = self.timestamp_format();
+        let layout = Layout {
+            timestamp_format: &timestamp_format,
+            level_labels: self.level_labels.as_deref(),
+            origin: self.origin.as_deref(),
+            timestamp_mode: self.timestamp_mode,
+            severity_map: self.severity_map.as_deref(),
+            started: Some(self.started),
+        };
+        match self.format {
+            LogFormat::Text => format::text(entry, &layout),
+            LogFormat::Json | LogFormat::Ndjson | LogFormat::JsonArray => {
+                format::json(entry, &layout)
+            }
+            LogFormat::Logfmt => format::logfmt(entry, &layout),
+            LogFormat::Csv => format::csv(entry, &layout),
+        }
+    }
 
-        match log_level {
-            LogLevel::Debug => {
-                for entry in self.entries {
-                    writeln!(
-                        file,
-                        "[{}] {:?}: {}",
-                        entry.timestap.format("%Y-%m-%d %H-%M-%S"),
-                        entry.level,
-                        entry.message
-                    )?;
+    fn tag_allowed(&self, entry: &LogEntry) -> bool {
+        match &entry.tag {
+            Some(tag) => self
+                .tag_filter
+                .as_ref()
+                .is_none_or(|tags| tags.contains(tag)),
+            None => !self.only_tagged,
+        }
+    }
+
+    fn mark_repeated(&self, line: &mut String, repeats: usize) {
+        match self.format {
+            LogFormat::Json | LogFormat::Ndjson | LogFormat::JsonArray
+                if self.formatter.is_none() =>
+            {
+                line.pop();
+                line.push_str(&format!(",\"repeated\":{}}}", repeats));
+            }
+            LogFormat::Logfmt if self.formatter.is_none() => {
+                line.push_str(&format!(" repeated={}", repeats));
+            }
+            // Keep the suffix inside the quoted message field.
+            LogFormat::Csv if self.formatter.is_none() && line.ends_with('"') => {
+                line.pop();
+                line.push_str(&format!(" (repeated {} times)\"", repeats));
+            }
+            _ => line.push_str(&format!(" (repeated {} times)", repeats)),
+        }
+    }
+
+    /// Writes buffered entries to the log file and clears the buffer, so the
+    /// logger can keep being used afterwards. The file stays open for the next
+    /// flush; see [`reopen`](Self::reopen).
+    pub fn flush(&mut self) -> Result<(), LoggerError> {
+        self.flush_and_clear().map(|_| ())
+    }
+
+    /// Like [`flush`](Self::flush), which clears the buffer too, but returns
+    /// how many lines made it to the log file. Entries filtered out by level
+    /// or tag aren't counted, and collapsed duplicates count once.
+    pub fn flush_and_clear(&mut self) -> Result<usize, LoggerError> {
+        let mut files = self
+            .log_files()
+            .with_routes(self.routes.clone())
+            .with_fallbacks(self.fallback_paths());
+        let mut result = self.write_entries(&mut files);
+        if let Some(fallback) = files.take_fallback() {
+            let message = format!(
+                "could not open {} ({}), logging to {} instead",
+                self.file_path().display(),
+                fallback.error,
+                fallback.path.display()
+            );
+            // The entry may be filtered out, but the switch shouldn't go unnoticed.
+            if env::var_os(QUIET_ENV_VAR).is_none() {
+                eprintln!("simple_logger: {}", message);
+            }
+            self.buffer(LogEntry::new(self.now(), LogLevel::Warn, message));
+            self.fallback = Some(fallback.path);
+            result = result.and_then(|written| Ok(written + self.write_entries(&mut files)?));
+        }
+        self.files = files.into_open_files();
+        result
+    }
+
+    /// Like [`flush`](Self::flush), but only writes the entries logged from
+    /// `start` to `end`, inclusive. The others stay buffered, in order.
+    pub fn flush_between<Tz: TimeZone>(
+        &mut self,
+        start: DateTime<Tz>,
+        end: DateTime<Tz>,
+    ) -> Result<(), LoggerError> {
+        let (start, end) = (start.fixed_offset(), end.fixed_offset());
+        let inside = |entry: &LogEntry| entry.timestamp >= start && entry.timestamp <= end;
+        // Each entry remembers where it was, so whatever a failed flush
+        // leaves behind goes back to the same place.
+        let mut window = Vec::new();
+        let mut window_at = Vec::new();
+        let mut entries = Vec::new();
+        for (index, entry) in mem::take(&mut self.entries).into_iter().enumerate() {
+            if inside(&entry) {
+                window_at.push((index, entry.timestamp, entry.level, entry.message.clone()));
+                window.push(entry);
+            } else {
+                entries.push((index, entry));
+            }
+        }
+        self.entries = window;
+        let result = self.flush();
+
+        // Put back whatever is left, all of the window if the flush failed.
+        // Entries the flush added itself, like the fallback notice, go last.
+        let mut added = Vec::new();
+        let mut next = 0;
+        for entry in mem::take(&mut self.entries) {
+            let found = window_at[next..]
+                .iter()
+                .position(|(_, timestamp, level, message)| {
+                    *timestamp == entry.timestamp
+                        && *level == entry.level
+                        && *message == entry.message
+                });
+            match found {
+                Some(offset) => {
+                    next += offset + 1;
+                    entries.push((window_at[next - 1].0, entry));
                 }
+                None => added.push(entry),
             }
-            LogLevel::Error => {
-                for entry in self.entries {
-                    match entry.level {
-                        LogLevel::Error => {
-                            writeln!(
-                                file,
-                                "[{}] {:?}: {}",
-                                entry.timestap.format("%Y-%m-%d %H-%M-%S"),
-                                entry.level,
-                                entry.message
-                            )?;
-                        }
-                        _ => continue,
-                    }
+        }
+        entries.sort_by_key(|(index, _)| *index);
+        let mut entries: Vec<LogEntry> = entries.into_iter().map(|(_, entry)| entry).collect();
+        entries.extend(added);
+        if !entries.is_empty() {
+            self.flushed.store(false, Ordering::Relaxed);
+        }
+        self.entries = entries;
+        result
+    }
+
+    /// Flushes the remaining entries, then ends the log file with a
+    /// `# session ended ...` line counting every entry logged by level and the
+    /// time elapsed since the first one.
+    pub fn finalize(mut self) -> Result<(), LoggerError> {
+        self.flush()?;
+        if self.disabled {
+            return Ok(());
+        }
+        let now = self.now();
+        let elapsed = self
+            .first_logged
+            .and_then(|first| (now - first).to_std().ok())
+            .unwrap_or_default();
+        let mut line = format::session_footer(&self.totals, elapsed);
+        line.push_str(self.line_ending.as_str());
+
+        // The footer belongs in the main file, whatever the level routing.
+        let mut files = self.log_files();
+        let footer = LogEntry::new(now, LogLevel::Default, "");
+        let result = files
+            .write_line(0, &footer, &line)
+            .and_then(|()| files.finish());
+        self.files = files.into_open_files();
+        result
+    }
+
+    fn fallback_paths(&self) -> Vec<PathBuf> {
+        let file_path = self.file_path();
+        let file_name = file_path.file_name().unwrap_or_default();
+        self.fallbacks
+            .iter()
+            .map(|path| {
+                if is_directory(path) {
+                    path.join(file_name)
+                } else {
+                    path.clone()
                 }
+            })
+            .collect()
+    }
+
+    fn log_files(&mut self) -> LogFiles {
+        LogFiles::new(
+            self.fallback.clone().unwrap_or_else(|| self.file_path()),
+            self.rotation,
+            self.max_file_size,
+            self.max_backups,
+        )
+        .with_header(self.header_line())
+        .with_sync(self.sync)
+        .with_batching(self.batch_writes)
+        .with_compression(self.compress_backups)
+        .with_open_files(mem::take(&mut self.files))
+    }
+
+    /// Closes the log file and opens it again, so entries go to a new file at
+    /// the configured path once `logrotate` or similar has moved the old one.
+    /// Call it from e.g. a `SIGHUP` handler.
+    pub fn reopen(&mut self) -> Result<(), LoggerError> {
+        self.fallback = None;
+        let header = self.header_line();
+        Ok(self.files.reopen(header.as_deref())?)
+    }
+
+    fn header_line(&self) -> Option<String> {
+        let mut lines = Vec::new();
+        if self.session_header {
+            lines.push(format::session_header(process::id(), &self.started));
+        }
+        match self.format {
+            LogFormat::Csv if self.formatter.is_none() => {
+                lines.push(String::from(format::CSV_HEADER));
             }
-            LogLevel::Info => {
-                for entry in self.entries {
-                    match entry.level {
-                        LogLevel::Info => {
-                            writeln!(
-                                file,
-                                "[{}] {:?}: {}",
-                                entry.timestap.format("%Y-%m-%d %H-%M-%S"),
-                                entry.level,
-                                entry.message
-                            )?;
-                        }
-                        _ => continue,
-                    }
+            LogFormat::Ndjson if self.header => lines.push(format::ndjson_header(
+                &app_name(),
+                process::id(),
+                &self.started,
+            )),
+            _ => {}
+        }
+        let mut header = String::new();
+        if self.bom {
+            header.push('\u{feff}');
+        }
+        for line in lines {
+            header.push_str(&line);
+            header.push_str(self.line_ending.as_str());
+        }
+        (!header.is_empty()).then_some(header)
+    }
+
+    /// Like [`flush`](Self::flush), but writes the filtered and formatted
+    /// entries to `writer` instead of the log file.
+    pub fn flush_to_writer<W: Write>(&mut self, writer: &mut W) -> Result<(), LoggerError> {
+        self.write_entries(&mut WriterDestination {
+            writer,
+            pending: Pending::default(),
+            batch: self.batch_writes,
+        })
+        .map(|_| ())
+    }
+
+    /// Returns the number of lines written.
+    fn write_entries(&mut self, destination: &mut impl Destination) -> Result<usize, LoggerError> {
+        let now = self.now();
+        let summaries: Vec<LogEntry> = self
+            .rate_limits
+            .iter_mut()
+            .filter_map(|limit| limit.summary(now))
+            .collect();
+        for summary in summaries {
+            self.buffer(summary);
+        }
+        if self.flushed.load(Ordering::Relaxed) {
+            return Ok(0);
+        }
+        if self.sorted_flush {
+            self.sort_by_time();
+        }
+
+        let log_level = self.log_level();
+        let mut entries = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.level >= log_level && self.tag_allowed(entry))
+            .peekable();
+        // Rendered up front, so a retry picks up from the first line that failed.
+        // Each line also records the range of entries it covers.
+        let mut lines = Vec::new();
+        while let Some((index, entry)) = entries.next() {
+            let mut repeats = 1;
+            let mut end = index + 1;
+            if self.dedup {
+                while let Some((index, _)) = entries
+                    .next_if(|(_, next)| next.level == entry.level && next.message == entry.message)
+                {
+                    repeats += 1;
+                    end = index + 1;
                 }
             }
-            LogLevel::Default => {
-                for entry in self.entries {
-                    writeln!(
-                        file,
-                        "[{}] {:?}: {}",
-                        entry.timestap.format("%Y-%m-%d %H-%M-%S"),
-                        entry.level,
-                        entry.message
-                    )?;
+            let mut line = self.render(entry);
+            if repeats > 1 {
+                self.mark_repeated(&mut line, repeats);
+            }
+            line.push_str(self.line_ending.as_str());
+            lines.push((entry, line, index..end));
+        }
+        if self.format == LogFormat::JsonArray && self.formatter.is_none() {
+            let last = lines.len().saturating_sub(1);
+            for (index, (_, line, _)) in lines.iter_mut().enumerate() {
+                line.truncate(line.len() - self.line_ending.as_str().len());
+                if index == 0 {
+                    line.insert(0, '[');
+                }
+                if index == last {
+                    line.push(']');
+                    line.push_str(self.line_ending.as_str());
+                } else {
+                    line.push(',');
                 }
             }
         }
 
+        let mut written = 0;
+        let mut retries = 0;
+        loop {
+            let result = lines[written..]
+                .iter()
+                .try_for_each(|(entry, line, _)| {
+                    destination.write_line(written, entry, line)?;
+                    written += 1;
+                    Ok(())
+                })
+                .and_then(|()| destination.finish());
+            match result {
+                Ok(()) => break,
+                Err(_) if retries < self.flush_retries => {
+                    retries += 1;
+                    thread::sleep(self.retry_delay);
+                }
+                Err(err) => {
+                    // Entries whose lines didn't make it out stay buffered,
+                    // in order, for the next flush. The rest are written.
+                    let mut unwritten = destination.take_unwritten();
+                    unwritten.extend(written..lines.len());
+                    let keep: Vec<Range<usize>> = unwritten
+                        .iter()
+                        .map(|&line| lines[line].2.clone())
+                        .collect();
+                    self.unwritten = unwritten.len();
+                    let entries = mem::take(&mut self.entries);
+                    self.entries = entries
+                        .into_iter()
+                        .enumerate()
+                        .filter(|(index, _)| keep.iter().any(|range| range.contains(index)))
+                        .map(|(_, entry)| entry)
+                        .collect();
+                    return Err(err);
+                }
+            }
+        }
+
+        // Sink failures don't mean the entries need writing again.
+        self.unwritten = 0;
+        self.entries.clear();
         self.flushed.store(true, Ordering::Relaxed);
+        if let Some(console) = &mut self.console {
+            console.flush().map_err(LoggerError::Sink)?;
+        }
+        for (_, sink) in &mut self.sinks {
+            sink.flush().map_err(LoggerError::Sink)?;
+        }
+        Ok(written)
+    }
+}
+
+/// Where [`Logger::write_entries`] sends rendered lines.
+trait Destination {
+    /// Takes the line with the given index, counting from 0 each flush.
+    fn write_line(&mut self, index: usize, entry: &LogEntry, line: &str)
+        -> Result<(), LoggerError>;
+
+    /// Called once every line has been written.
+    fn finish(&mut self) -> Result<(), LoggerError>;
+
+    /// After a failed write, drops whatever is still pending and returns the
+    /// indices of the lines that didn't make it out, in order.
+    fn take_unwritten(&mut self) -> Vec<usize>;
+}
+
+struct WriterDestination<'a, W> {
+    writer: &'a mut W,
+    pending: Pending,
+    batch: bool,
+}
+
+impl<W: Write> Destination for WriterDestination<'_, W> {
+    fn write_line(
+        &mut self,
+        index: usize,
+        _entry: &LogEntry,
+        line: &str,
+    ) -> Result<(), LoggerError> {
+        self.pending
+            .push_line(self.writer, index, line.as_bytes(), self.batch)?;
         Ok(())
     }
+
+    fn finish(&mut self) -> Result<(), LoggerError> {
+        self.pending.write(self.writer)?;
+        Ok(self.writer.flush()?)
+    }
+
+    fn take_unwritten(&mut self) -> Vec<usize> {
+        self.pending.take_unwritten()
+    }
+}
+
+/// Writes to [`default_log_path`], with the level read from `LOG_LEVEL` at flush time.
+impl Default for Logger {
+    fn default() -> Self {
+        Logger::with_file_path(None)
+    }
+}
+
+/// Logs every non-empty line written as a separate entry at the
+/// [write level](Logger::with_write_level), so the logger can stand in for
+/// writer-based APIs. A line written in several calls becomes one entry once
+/// its `\n` arrives, or when the logger is flushed.
+impl Write for Logger {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.partial_line.extend_from_slice(buf);
+        if let Some(end) = self.partial_line.iter().rposition(|&byte| byte == b'\n') {
+            let lines: Vec<u8> = self.partial_line.drain(..=end).collect();
+            for line in String::from_utf8_lossy(&lines).lines() {
+                if !line.is_empty() {
+                    self.log(self.write_level, line.to_string());
+                }
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.log_partial_line();
+        Ok(Logger::flush(self)?)
+    }
+}
+
+/// Buffers entries built elsewhere as they are, without echoing them to the
+/// console or sinks, which already saw them in the logger that created them.
+impl Extend<LogEntry> for Logger {
+    fn extend<I: IntoIterator<Item = LogEntry>>(&mut self, entries: I) {
+        for mut entry in entries {
+            if !self.records(entry.level) {
+                continue;
+            }
+            self.redact(&mut entry);
+            self.totals.add(entry.level);
+            self.first_logged = Some(match self.first_logged {
+                Some(first) => first.min(entry.timestamp),
+                None => entry.timestamp,
+            });
+            self.entries.push(entry);
+            self.flushed.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Drop for Logger {
+    fn drop(&mut self) {
+        self.log_partial_line();
+        if self.flushed.load(Ordering::Relaxed) || self.entries.is_empty() {
+            return;
+        }
+        if let Err(err) = self.flush() {
+            if self.unwritten > 0 && env::var_os(QUIET_ENV_VAR).is_none() {
+                eprintln!(
+                    "simple_logger: lost {} log entries on drop: {}",
+                    self.unwritten, err
+                );
+            }
+        }
+    }
 }
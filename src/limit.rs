@@ -0,0 +1,91 @@
+use crate::{LogEntry, LogLevel};
+use chrono::{DateTime, FixedOffset, TimeDelta};
+
+/// Caps how many entries at one level are kept per one-second window.
+pub(crate) struct RateLimit {
+    pub(crate) level: LogLevel,
+    max: u32,
+    window_start: Option<DateTime<FixedOffset>>,
+    count: u32,
+    suppressed: u64,
+}
+
+impl RateLimit {
+    pub(crate) fn new(level: LogLevel, max: u32) -> Self {
+        RateLimit {
+            level,
+            max,
+            window_start: None,
+            count: 0,
+            suppressed: 0,
+        }
+    }
+
+    /// The same limit, with nothing counted yet.
+    pub(crate) fn reset(&self) -> Self {
+        RateLimit::new(self.level, self.max)
+    }
+
+    /// Decides whether `entry` is kept, returning the summary of the previous
+    /// window first if `entry` starts a new one.
+    pub(crate) fn admit(&mut self, entry: &LogEntry) -> (bool, Option<LogEntry>) {
+        let mut summary = None;
+        let in_window = self
+            .window_start
+            .is_some_and(|start| entry.timestamp - start < TimeDelta::seconds(1));
+        if !in_window {
+            summary = self.summary(entry.timestamp);
+            self.window_start = Some(entry.timestamp);
+            self.count = 0;
+        }
+
+        if self.count < self.max {
+            self.count += 1;
+            (true, summary)
+        } else {
+            self.suppressed += 1;
+            (false, summary)
+        }
+    }
+
+    /// Reports the entries dropped so far in the current window, if any.
+    pub(crate) fn summary(&mut self, timestamp: DateTime<FixedOffset>) -> Option<LogEntry> {
+        if self.suppressed == 0 {
+            return None;
+        }
+        let message = format!(
+            "suppressed {} {} entries over the rate limit",
+            self.suppressed, self.level
+        );
+        self.suppressed = 0;
+        Some(LogEntry::new(timestamp, self.level, message))
+    }
+}
+
+/// Keeps the first of every `every` entries at one level.
+pub(crate) struct Sample {
+    pub(crate) level: LogLevel,
+    every: u32,
+    seen: u32,
+}
+
+impl Sample {
+    pub(crate) fn new(level: LogLevel, every: u32) -> Self {
+        Sample {
+            level,
+            every: every.max(1),
+            seen: 0,
+        }
+    }
+
+    /// The same sampling, starting over.
+    pub(crate) fn reset(&self) -> Self {
+        Sample::new(self.level, self.every)
+    }
+
+    pub(crate) fn admit(&mut self) -> bool {
+        let keep = self.seen == 0;
+        self.seen = (self.seen + 1) % self.every;
+        keep
+    }
+}
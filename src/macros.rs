@@ -0,0 +1,107 @@
+/// Logs a formatted message at a level along with the call site, skipping the
+/// formatting entirely when the level is below the logger's threshold.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log {
+    ($logger:expr, $level:expr, $($arg:tt)+) => {{
+        let logger = &mut $logger;
+        if $level >= $crate::STATIC_MAX_LEVEL && logger.enabled($level) {
+            // Messages without arguments are `&'static str` and needn't be copied.
+            let message: ::std::borrow::Cow<'static, str> = match ::std::format_args!($($arg)+) {
+                args => match args.as_str() {
+                    ::std::option::Option::Some(message) => ::std::borrow::Cow::Borrowed(message),
+                    ::std::option::Option::None => ::std::borrow::Cow::Owned(args.to_string()),
+                },
+            };
+            logger.__record_at($level, message, ::std::file!(), ::std::line!());
+        }
+    }};
+}
+
+/// Logs a formatted message at [`LogLevel::Trace`](crate::LogLevel::Trace).
+///
+/// ```
+/// use simple_logger::{trace, Logger};
+///
+/// let mut logger = Logger::builder()
+///     .file_path(std::env::temp_dir().join("app.log"))
+///     .build();
+/// trace!(logger, "tick {}", 1);
+/// ```
+#[macro_export]
+macro_rules! trace {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::__log!($logger, $crate::LogLevel::Trace, $($arg)+)
+    };
+}
+
+/// Logs a formatted message at [`LogLevel::Debug`](crate::LogLevel::Debug).
+///
+/// ```
+/// use simple_logger::{debug, Logger};
+///
+/// let mut logger = Logger::builder()
+///     .file_path(std::env::temp_dir().join("app.log"))
+///     .build();
+/// let retries = 3;
+/// debug!(logger, "retrying ({retries} left)");
+/// ```
+#[macro_export]
+macro_rules! debug {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::__log!($logger, $crate::LogLevel::Debug, $($arg)+)
+    };
+}
+
+/// Logs a formatted message at [`LogLevel::Info`](crate::LogLevel::Info).
+///
+/// ```
+/// use simple_logger::{info, Logger};
+///
+/// let mut logger = Logger::builder()
+///     .file_path(std::env::temp_dir().join("app.log"))
+///     .build();
+/// let id = 42;
+/// info!(logger, "user {} logged in", id);
+/// ```
+#[macro_export]
+macro_rules! info {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::__log!($logger, $crate::LogLevel::Info, $($arg)+)
+    };
+}
+
+/// Logs a formatted message at [`LogLevel::Warn`](crate::LogLevel::Warn).
+///
+/// ```
+/// use simple_logger::{warn, Logger};
+///
+/// let mut logger = Logger::builder()
+///     .file_path(std::env::temp_dir().join("app.log"))
+///     .build();
+/// warn!(logger, "disk {}% full", 91);
+/// ```
+#[macro_export]
+macro_rules! warn {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::__log!($logger, $crate::LogLevel::Warn, $($arg)+)
+    };
+}
+
+/// Logs a formatted message at [`LogLevel::Error`](crate::LogLevel::Error).
+///
+/// ```
+/// use simple_logger::{error, Logger};
+///
+/// let mut logger = Logger::builder()
+///     .file_path(std::env::temp_dir().join("app.log"))
+///     .build();
+/// let path = "/etc/app.toml";
+/// error!(logger, "failed to read {path}: {}", "permission denied");
+/// ```
+#[macro_export]
+macro_rules! error {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::__log!($logger, $crate::LogLevel::Error, $($arg)+)
+    };
+}
@@ -0,0 +1,34 @@
+use std::borrow::Cow;
+
+/// Anything the logging methods accept as a message.
+///
+/// Owned strings are moved into the entry and `Cow::Borrowed` literals are
+/// stored without being copied. Borrowed `&str`s are copied, since they may
+/// not live as long as the entry.
+pub trait IntoMessage {
+    fn into_message(self) -> Cow<'static, str>;
+}
+
+impl IntoMessage for &str {
+    fn into_message(self) -> Cow<'static, str> {
+        Cow::Owned(self.to_owned())
+    }
+}
+
+impl IntoMessage for &String {
+    fn into_message(self) -> Cow<'static, str> {
+        Cow::Owned(self.clone())
+    }
+}
+
+impl IntoMessage for String {
+    fn into_message(self) -> Cow<'static, str> {
+        Cow::Owned(self)
+    }
+}
+
+impl IntoMessage for Cow<'static, str> {
+    fn into_message(self) -> Cow<'static, str> {
+        self
+    }
+}
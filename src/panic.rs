@@ -0,0 +1,59 @@
+use crate::{thread_name, Logger, SharedLogger};
+use std::ops::Deref;
+use std::panic;
+use std::sync::TryLockError;
+use std::thread;
+use std::time::Duration;
+
+impl Logger {
+    /// Logs every panic as an error entry and flushes it right away, before
+    /// handing the panic on to the previously installed hook.
+    ///
+    /// Takes anything that derefs to a [`SharedLogger`], such as an
+    /// `Arc<SharedLogger>` or [`Logger::global`].
+    pub fn install_panic_hook<L>(logger: L)
+    where
+        L: Deref<Target = SharedLogger> + Send + Sync + 'static,
+    {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let payload = info.payload();
+            let payload = payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("Box<dyn Any>");
+            let message = match info.location() {
+                Some(location) => format!(
+                    "thread '{}' panicked at {}: {}",
+                    thread_name(),
+                    location,
+                    payload
+                ),
+                None => format!("thread '{}' panicked: {}", thread_name(), payload),
+            };
+            record_panic(&logger, message);
+            previous(info);
+        }));
+    }
+}
+
+/// Logs and flushes `message`, giving up if the logger stays locked for long:
+/// the panicking thread could be the one holding it.
+fn record_panic(logger: &SharedLogger, message: String) {
+    for _ in 0..100 {
+        let mut logger = match logger.logger.try_lock() {
+            Ok(logger) => logger,
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(TryLockError::WouldBlock) => {
+                thread::sleep(Duration::from_millis(1));
+                continue;
+            }
+        };
+        logger.error(message);
+        if let Err(err) = logger.flush() {
+            eprintln!("simple_logger: failed to flush log entries: {}", err);
+        }
+        return;
+    }
+}
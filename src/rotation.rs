@@ -0,0 +1,485 @@
+use crate::{Destination, LogEntry, LogLevel, LoggerError};
+use chrono::NaiveDate;
+use std::fs::{self, create_dir_all, File, OpenOptions};
+use std::io::{self, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+
+/// When to start a new log file, independently of [`max_file_size`](crate::LoggerBuilder::max_file_size).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    #[default]
+    Never,
+    /// One file per calendar day, e.g. `app.log` becomes `app-2024-06-01.log`.
+    /// Entries go to the file for the day they were logged, not the day they're flushed.
+    Daily,
+}
+
+/// The log file(s) a flush writes to, applying daily and size-based rotation.
+pub(crate) struct LogFiles {
+    path: PathBuf,
+    rotation: Rotation,
+    max_file_size: Option<u64>,
+    max_backups: usize,
+    header: Option<String>,
+    sync: bool,
+    compress: bool,
+    routes: Vec<(LogLevel, PathBuf)>,
+    batch: bool,
+    fallbacks: Vec<PathBuf>,
+    fallback: Option<Fallback>,
+    cached: Vec<ActiveFile>,
+    open: Vec<ActiveFile>,
+}
+
+/// The log file couldn't be opened, so lines went to `path` instead.
+pub(crate) struct Fallback {
+    pub(crate) path: PathBuf,
+    pub(crate) error: LoggerError,
+}
+
+/// Files kept open from one flush to the next.
+#[derive(Default)]
+pub(crate) struct OpenFiles(Vec<ActiveFile>);
+
+impl OpenFiles {
+    /// Closes every file and opens its path again, creating it if it was moved away.
+    pub(crate) fn reopen(&mut self, header: Option<&str>) -> io::Result<()> {
+        let paths: Vec<PathBuf> = self.0.drain(..).map(|file| file.path).collect();
+        for path in paths {
+            self.0.push(ActiveFile::open(path, header)?);
+        }
+        Ok(())
+    }
+}
+
+impl LogFiles {
+    pub(crate) fn new(
+        path: PathBuf,
+        rotation: Rotation,
+        max_file_size: Option<u64>,
+        max_backups: usize,
+    ) -> Self {
+        LogFiles {
+            path,
+            rotation,
+            max_file_size,
+            max_backups,
+            header: None,
+            sync: false,
+            compress: false,
+            routes: Vec::new(),
+            batch: true,
+            fallbacks: Vec::new(),
+            fallback: None,
+            cached: Vec::new(),
+            open: Vec::new(),
+        }
+    }
+
+    /// Calls `sync_all` on each file once its lines are written.
+    pub(crate) fn with_sync(mut self, sync: bool) -> Self {
+        self.sync = sync;
+        self
+    }
+
+    /// Writes to `files` instead of opening them again.
+    pub(crate) fn with_open_files(mut self, files: OpenFiles) -> Self {
+        self.cached = files.0;
+        self
+    }
+
+    /// The files to keep open for the next flush: the ones written to this
+    /// time, so e.g. yesterday's file is closed under daily rotation.
+    pub(crate) fn into_open_files(mut self) -> OpenFiles {
+        if self.open.is_empty() {
+            return OpenFiles(self.cached);
+        }
+        for file in &mut self.open {
+            file.unlock();
+        }
+        // A file closed here may still hold the header it was created with.
+        for file in &mut self.cached {
+            let _ = file.write_pending();
+        }
+        OpenFiles(self.open)
+    }
+
+    /// Gzips each backup as it's rotated out.
+    pub(crate) fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Sends entries at the given levels to their own path instead.
+    pub(crate) fn with_routes(mut self, routes: Vec<(LogLevel, PathBuf)>) -> Self {
+        self.routes = routes;
+        self
+    }
+
+    /// Writes each file's lines in one go when finishing, rather than one at a time.
+    pub(crate) fn with_batching(mut self, batch: bool) -> Self {
+        self.batch = batch;
+        self
+    }
+
+    /// Tries each of `paths` in turn when the main log file can't be opened.
+    pub(crate) fn with_fallbacks(mut self, paths: Vec<PathBuf>) -> Self {
+        self.fallbacks = paths;
+        self
+    }
+
+    /// The fallback taken this flush, if the main log file couldn't be opened.
+    pub(crate) fn take_fallback(&mut self) -> Option<Fallback> {
+        self.fallback.take()
+    }
+
+    /// Writes `header`, terminator included, at the start of every file this creates.
+    pub(crate) fn with_header(mut self, header: Option<String>) -> Self {
+        self.header = header;
+        self
+    }
+}
+
+impl Destination for LogFiles {
+    fn write_line(
+        &mut self,
+        index: usize,
+        entry: &LogEntry,
+        line: &str,
+    ) -> Result<(), LoggerError> {
+        let route = self
+            .routes
+            .iter()
+            .find(|(level, _)| *level == entry.level)
+            .map(|(_, path)| path.clone());
+        let position = match route {
+            Some(path) => self.file_index(&path, entry)?,
+            None => match self.file_index(&self.path.clone(), entry) {
+                Ok(position) => position,
+                Err(error) => self.fall_back(entry, error)?,
+            },
+        };
+        let file = &mut self.open[position];
+
+        if let Some(max_file_size) = self.max_file_size {
+            if file.size > 0 && file.size + line.len() as u64 > max_file_size {
+                file.rotate(self.max_backups, self.compress, self.header.as_deref())
+                    .map_err(LoggerError::Rotation)?;
+            }
+        }
+
+        file.write_line(index, line.as_bytes(), self.batch)?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), LoggerError> {
+        for file in &mut self.open {
+            file.finish(self.sync)?;
+        }
+        Ok(())
+    }
+
+    fn take_unwritten(&mut self) -> Vec<usize> {
+        let mut unwritten: Vec<usize> = self
+            .open
+            .iter_mut()
+            .flat_map(ActiveFile::take_unwritten)
+            .collect();
+        unwritten.sort_unstable();
+        unwritten
+    }
+}
+
+impl LogFiles {
+    /// Finds or opens the file `entry` belongs in when logging to `path`.
+    fn file_index(&mut self, path: &Path, entry: &LogEntry) -> Result<usize, LoggerError> {
+        let target = match self.rotation {
+            Rotation::Never => path.to_path_buf(),
+            Rotation::Daily => dated_path(path, entry.timestamp.date_naive()),
+        };
+        if let Some(index) = self.open.iter().position(|file| file.path == target) {
+            return Ok(index);
+        }
+        let mut file = match self.cached.iter().position(|file| file.path == target) {
+            Some(index) => self.cached.swap_remove(index),
+            None if target.is_dir() => return Err(LoggerError::IsDirectory(target)),
+            None => ActiveFile::open(target, self.header.as_deref())?,
+        };
+        file.lock()?;
+        self.open.push(file);
+        Ok(self.open.len() - 1)
+    }
+
+    /// Switches the main log file to the first fallback that can be opened,
+    /// returning `error` if none can.
+    fn fall_back(&mut self, entry: &LogEntry, error: LoggerError) -> Result<usize, LoggerError> {
+        for path in mem::take(&mut self.fallbacks) {
+            if let Ok(index) = self.file_index(&path, entry) {
+                self.path = path.clone();
+                self.fallback = Some(Fallback { path, error });
+                return Ok(index);
+            }
+        }
+        Err(error)
+    }
+}
+
+/// A log file opened for appending, tracking its size for rotation.
+///
+/// Lines are collected in memory and written with as few calls as possible
+/// by [`write_pending`](Self::write_pending).
+struct ActiveFile {
+    path: PathBuf,
+    size: u64,
+    file: File,
+    pending: Pending,
+}
+
+impl ActiveFile {
+    fn open(path: PathBuf, header: Option<&str>) -> io::Result<Self> {
+        let created = !path.exists();
+        let file = open(&path)?;
+        let size = file.metadata()?.len();
+        let mut active = ActiveFile {
+            path,
+            size,
+            file,
+            pending: Pending::default(),
+        };
+        if created {
+            active.write_header(header);
+        }
+        Ok(active)
+    }
+
+    fn rotate(
+        &mut self,
+        max_backups: usize,
+        compress: bool,
+        header: Option<&str>,
+    ) -> io::Result<()> {
+        self.write_pending()?;
+        rotate(&self.path, max_backups, compress)?;
+        self.file = open(&self.path)?;
+        self.lock()?;
+        self.write_header(header);
+        Ok(())
+    }
+
+    /// Takes an exclusive advisory lock on the file, so processes sharing it
+    /// take turns writing instead of interleaving partial lines. Held until
+    /// [`unlock`](Self::unlock), or until the file is closed.
+    fn lock(&mut self) -> io::Result<()> {
+        match self.file.lock() {
+            // Some filesystems don't support locking; write unlocked there.
+            Err(err) if err.kind() == io::ErrorKind::Unsupported => {}
+            result => result?,
+        }
+        // Other processes may have written to the file since it was opened.
+        self.size = self.file.metadata()?.len() + self.pending.bytes.len() as u64;
+        Ok(())
+    }
+
+    fn unlock(&mut self) {
+        // Whatever is still pending has to land before another process writes.
+        let _ = self.write_pending();
+        let _ = self.file.unlock();
+    }
+
+    fn finish(&mut self, sync: bool) -> io::Result<()> {
+        self.write_pending()?;
+        if sync {
+            self.file.sync_all()?;
+        }
+        Ok(())
+    }
+
+    fn write_header(&mut self, header: Option<&str>) {
+        if let Some(header) = header {
+            self.write_all(header.as_bytes());
+        }
+    }
+
+    fn write_all(&mut self, bytes: &[u8]) {
+        self.pending.bytes.extend_from_slice(bytes);
+        self.size += bytes.len() as u64;
+    }
+
+    fn write_line(&mut self, index: usize, line: &[u8], batch: bool) -> io::Result<()> {
+        self.pending.push_line(&mut self.file, index, line, batch)?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    fn write_pending(&mut self) -> io::Result<()> {
+        self.pending.write(&mut self.file)
+    }
+
+    fn take_unwritten(&mut self) -> Vec<usize> {
+        let before = self.pending.bytes.len();
+        let unwritten = self.pending.take_unwritten();
+        self.size -= (before - self.pending.bytes.len()) as u64;
+        unwritten
+    }
+}
+
+/// Bytes waiting to be written, along with the lines they came from.
+#[derive(Default)]
+pub(crate) struct Pending {
+    bytes: Vec<u8>,
+    /// Index and length of each line queued since everything was last written.
+    lines: Vec<(usize, usize)>,
+}
+
+impl Pending {
+    /// Queues `line` and, unless batching, writes everything out. A failed
+    /// write hands back a line none of which made it out, so retrying never
+    /// queues it twice. Once part of it is out, the rest stays pending.
+    pub(crate) fn push_line(
+        &mut self,
+        writer: &mut impl Write,
+        index: usize,
+        line: &[u8],
+        batch: bool,
+    ) -> io::Result<()> {
+        self.bytes.extend_from_slice(line);
+        if !batch {
+            if let Err(err) = write_pending(writer, &mut self.bytes) {
+                if self.bytes.len() >= line.len() {
+                    self.bytes.truncate(self.bytes.len() - line.len());
+                    return Err(err);
+                }
+            }
+        }
+        if self.bytes.is_empty() {
+            self.lines.clear();
+        } else {
+            self.lines.push((index, line.len()));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn write(&mut self, writer: &mut impl Write) -> io::Result<()> {
+        write_pending(writer, &mut self.bytes)?;
+        self.lines.clear();
+        Ok(())
+    }
+
+    /// Gives up on the lines a failed write didn't get all of, dropping their
+    /// bytes so they're never written alongside a later attempt. Returns
+    /// their indices.
+    pub(crate) fn take_unwritten(&mut self) -> Vec<usize> {
+        // Queued lines are the tail of the pending bytes; a line partly out
+        // counts as unwritten, leaving a fragment rather than losing its end.
+        let mut unwritten = Vec::new();
+        while let Some((index, len)) = self.lines.pop() {
+            if self.bytes.is_empty() {
+                break;
+            }
+            let cut = len.min(self.bytes.len());
+            self.bytes.truncate(self.bytes.len() - cut);
+            unwritten.push(index);
+        }
+        self.lines.clear();
+        unwritten.reverse();
+        unwritten
+    }
+}
+
+/// Writes out `pending`, usually in a single call. Whatever a failed write
+/// didn't get to stays pending, so retrying never writes a line twice.
+fn write_pending(writer: &mut impl Write, pending: &mut Vec<u8>) -> io::Result<()> {
+    while !pending.is_empty() {
+        match writer.write(pending) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(written) => {
+                pending.drain(..written);
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn open(path: &Path) -> io::Result<File> {
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        println!("Creating new log file at: {:?}", path);
+    }
+
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Moves `path` to `path.1` (gzipped to `path.1.gz` when `compress` is set),
+/// shifting older backups up by one and deleting whatever would land past
+/// `max_backups`. Compressed and plain backups share the same numbering.
+fn rotate(path: &Path, max_backups: usize, compress: bool) -> io::Result<()> {
+    if max_backups == 0 {
+        return ignore_missing(fs::remove_file(path));
+    }
+
+    for suffix in BACKUP_SUFFIXES {
+        ignore_missing(fs::remove_file(backup_path(path, max_backups, suffix)))?;
+    }
+    for index in (1..max_backups).rev() {
+        for suffix in BACKUP_SUFFIXES {
+            ignore_missing(fs::rename(
+                backup_path(path, index, suffix),
+                backup_path(path, index + 1, suffix),
+            ))?;
+        }
+    }
+    let backup = backup_path(path, 1, "");
+    ignore_missing(fs::rename(path, &backup))?;
+    if compress && backup.exists() {
+        gzip(&backup, &backup_path(path, 1, ".gz"))?;
+    }
+    Ok(())
+}
+
+const BACKUP_SUFFIXES: [&str; 2] = ["", ".gz"];
+
+#[cfg(feature = "compression")]
+fn gzip(source: &Path, target: &Path) -> io::Result<()> {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    let mut encoder = GzEncoder::new(File::create(target)?, Compression::default());
+    io::copy(&mut File::open(source)?, &mut encoder)?;
+    encoder.finish()?;
+    fs::remove_file(source)
+}
+
+// Compression can only be turned on with the `compression` feature.
+#[cfg(not(feature = "compression"))]
+fn gzip(_source: &Path, _target: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn dated_path(path: &Path, date: NaiveDate) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let mut name = format!("{}-{}", stem, date.format("%Y-%m-%d"));
+    if let Some(extension) = path.extension() {
+        name.push('.');
+        name.push_str(&extension.to_string_lossy());
+    }
+    path.with_file_name(name)
+}
+
+fn backup_path(path: &Path, index: usize, suffix: &str) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".{index}{suffix}"));
+    PathBuf::from(backup)
+}
+
+// A backup that's already gone (or never existed) is not an error.
+fn ignore_missing(result: io::Result<()>) -> io::Result<()> {
+    match result {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        other => other,
+    }
+}
@@ -0,0 +1,58 @@
+use crate::{IntoMessage, LogLevel, Logger, LoggerError};
+use std::sync::{Mutex, MutexGuard};
+
+/// A [`Logger`] behind a mutex, so it can be put in an `Arc` and logged to from several threads.
+pub struct SharedLogger {
+    pub(crate) logger: Mutex<Logger>,
+}
+
+impl SharedLogger {
+    pub fn new(logger: Logger) -> Self {
+        SharedLogger {
+            logger: Mutex::new(logger),
+        }
+    }
+
+    /// Locks the underlying logger. A panic on another thread while logging
+    /// doesn't poison it for everyone else.
+    pub fn lock(&self) -> MutexGuard<'_, Logger> {
+        self.logger
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Logs `message` at `level`, for when the level is only known at runtime.
+    pub fn log(&self, level: LogLevel, message: impl IntoMessage) {
+        self.lock().log(level, message);
+    }
+
+    pub fn trace(&self, message: impl IntoMessage) {
+        self.log(LogLevel::Trace, message);
+    }
+
+    pub fn debug(&self, message: impl IntoMessage) {
+        self.log(LogLevel::Debug, message);
+    }
+
+    pub fn info(&self, message: impl IntoMessage) {
+        self.log(LogLevel::Info, message);
+    }
+
+    pub fn warn(&self, message: impl IntoMessage) {
+        self.log(LogLevel::Warn, message);
+    }
+
+    pub fn error(&self, message: impl IntoMessage) {
+        self.log(LogLevel::Error, message);
+    }
+
+    pub fn flush(&self) -> Result<(), LoggerError> {
+        self.lock().flush()
+    }
+}
+
+impl From<Logger> for SharedLogger {
+    fn from(logger: Logger) -> Self {
+        SharedLogger::new(logger)
+    }
+}
@@ -0,0 +1,84 @@
+use crate::format::{self, Layout};
+use crate::{rotation, LogEntry};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// An extra destination that receives every entry as soon as it is logged,
+/// alongside the buffered log file written on [`flush`](crate::Logger::flush).
+pub trait Sink: Send {
+    fn write_entry(&mut self, entry: &LogEntry) -> io::Result<()>;
+
+    /// Called at the end of every [`Logger::flush`](crate::Logger::flush).
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Discards every entry, e.g. to measure what fanning out to a sink costs.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NullSink;
+
+impl Sink for NullSink {
+    fn write_entry(&mut self, _entry: &LogEntry) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Appends each entry to a file right away instead of waiting for a flush.
+pub struct FileSink {
+    file: File,
+}
+
+impl FileSink {
+    pub fn new(path: &Path) -> io::Result<Self> {
+        Ok(FileSink {
+            file: rotation::open(path)?,
+        })
+    }
+}
+
+impl Sink for FileSink {
+    fn write_entry(&mut self, entry: &LogEntry) -> io::Result<()> {
+        writeln!(self.file, "{}", format::text(entry, &Layout::default()))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Keeps formatted lines in memory, for asserting on what code under test logged.
+///
+/// Clones share the same lines, so keep one to read from after handing the
+/// other to [`Logger::with_memory_sink`](crate::Logger::with_memory_sink).
+#[derive(Debug, Default, Clone)]
+pub struct MemorySink {
+    lines: Arc<Mutex<Vec<String>>>,
+}
+
+impl MemorySink {
+    pub fn new() -> Self {
+        MemorySink::default()
+    }
+
+    /// Every line received so far, in the default text format.
+    pub fn lines(&self) -> Vec<String> {
+        self.lines
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+impl Sink for MemorySink {
+    fn write_entry(&mut self, entry: &LogEntry) -> io::Result<()> {
+        let line = format::text(entry, &Layout::default());
+        self.lines
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(line);
+        Ok(())
+    }
+}
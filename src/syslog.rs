@@ -0,0 +1,58 @@
+use crate::format::Origin;
+use crate::{app_name, LogEntry, Sink};
+use std::io;
+use std::os::unix::net::UnixDatagram;
+use std::path::Path;
+
+/// Facility code for user-level messages.
+const FACILITY_USER: u8 = 1;
+
+/// Forwards each entry to the local syslog daemon, framed as in RFC 3164:
+/// `<PRI>Mmm dd hh:mm:ss host app[pid]: message`.
+pub struct SyslogSink {
+    socket: UnixDatagram,
+    origin: Origin,
+    app: String,
+}
+
+impl SyslogSink {
+    /// Connects to the daemon at `/dev/log`.
+    pub fn new() -> io::Result<Self> {
+        SyslogSink::connect(Path::new("/dev/log"))
+    }
+
+    /// Connects to a daemon listening on another socket.
+    pub fn connect(path: &Path) -> io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(path)?;
+        Ok(SyslogSink {
+            socket,
+            origin: Origin::current(),
+            app: app_name(),
+        })
+    }
+
+    fn frame(&self, entry: &LogEntry) -> String {
+        let priority = FACILITY_USER * 8 + entry.level.syslog_severity();
+        let mut message = format!(
+            "<{}>{} {} {}[{}]: {}",
+            priority,
+            entry.timestamp.format("%b %e %H:%M:%S"),
+            self.origin.hostname,
+            self.app,
+            self.origin.pid,
+            entry.message
+        );
+        for (key, value) in &entry.fields {
+            message.push_str(&format!(" {}={}", key, value));
+        }
+        message
+    }
+}
+
+impl Sink for SyslogSink {
+    fn write_entry(&mut self, entry: &LogEntry) -> io::Result<()> {
+        self.socket.send(self.frame(entry).as_bytes())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use crate::format::{self, Layout};
+use crate::{rotation, LogEntry, Logger, Sink};
+use std::fs::File;
+use std::io::{self, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+const RECONNECT_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Sends each entry as a newline-terminated line to a TCP collector.
+///
+/// The connection is opened on the first entry and opened again whenever a
+/// write fails. After a failed connection attempt, no new one is made for a
+/// few seconds so logging doesn't block. Until then, entries go to the
+/// [fallback file](Self::with_fallback) if there is one, and are reported as
+/// errors otherwise.
+pub struct TcpSink {
+    addr: SocketAddr,
+    stream: Option<TcpStream>,
+    fallback: Option<File>,
+    failed_at: Option<Instant>,
+}
+
+impl TcpSink {
+    pub fn new(addr: SocketAddr) -> Self {
+        TcpSink {
+            addr,
+            stream: None,
+            fallback: None,
+            failed_at: None,
+        }
+    }
+
+    /// Appends entries to `path` while the collector is down.
+    pub fn with_fallback(mut self, path: &Path) -> io::Result<Self> {
+        self.fallback = Some(rotation::open(path)?);
+        Ok(self)
+    }
+
+    fn send(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if let Some(stream) = &mut self.stream {
+            if stream.write_all(bytes).is_ok() {
+                return Ok(());
+            }
+            // The collector went away; try once more on a new connection.
+            self.stream = None;
+        }
+        if let Some(failed_at) = self.failed_at {
+            if failed_at.elapsed() < RECONNECT_BACKOFF {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotConnected,
+                    "collector unreachable, waiting to reconnect",
+                ));
+            }
+        }
+        let result = TcpStream::connect_timeout(&self.addr, CONNECT_TIMEOUT)
+            .and_then(|mut stream| stream.write_all(bytes).map(|()| stream));
+        match result {
+            Ok(stream) => {
+                self.stream = Some(stream);
+                self.failed_at = None;
+                Ok(())
+            }
+            Err(err) => {
+                self.failed_at = Some(Instant::now());
+                Err(err)
+            }
+        }
+    }
+}
+
+impl Sink for TcpSink {
+    fn write_entry(&mut self, entry: &LogEntry) -> io::Result<()> {
+        let line = format!("{}\n", format::text(entry, &Layout::default()));
+        match self.send(line.as_bytes()) {
+            Ok(()) => Ok(()),
+            Err(err) => match &mut self.fallback {
+                Some(file) => file.write_all(line.as_bytes()),
+                None => Err(err),
+            },
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(file) = &mut self.fallback {
+            file.flush()?;
+        }
+        match &mut self.stream {
+            Some(stream) => stream.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Logger {
+    /// Sends every entry logged from now on to the TCP collector at `addr`,
+    /// see [`TcpSink`].
+    pub fn add_tcp_sink(&mut self, addr: SocketAddr) {
+        self.add_sink(TcpSink::new(addr));
+    }
+}
@@ -0,0 +1,119 @@
+use crate::format::{self, Layout};
+use crate::{LogEntry, LogLevel, Sink};
+use std::io;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// POSTs entries at or above a threshold to a webhook, e.g. for on-call alerts.
+///
+/// Requests are sent from a background thread, so logging never waits on the
+/// network, and failed requests are dropped. Entries logged within the
+/// [batch window](Self::with_batch_window) of each other share one request,
+/// whose JSON body has a `text` field with the formatted lines, for chat
+/// webhooks, and an `entries` array with each entry as in [`LogFormat::Json`](crate::LogFormat::Json).
+pub struct WebhookSink {
+    url: String,
+    threshold: LogLevel,
+    timeout: Duration,
+    batch_window: Duration,
+    worker: Option<(Sender<LogEntry>, JoinHandle<()>)>,
+}
+
+impl WebhookSink {
+    pub fn new(url: &str, threshold: LogLevel) -> Self {
+        WebhookSink {
+            url: url.to_string(),
+            threshold,
+            timeout: Duration::from_secs(5),
+            batch_window: Duration::from_millis(500),
+            worker: None,
+        }
+    }
+
+    /// Gives up on a request after `timeout`. Defaults to 5 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// How long to wait for more entries before sending a request. Defaults
+    /// to 500 milliseconds.
+    pub fn with_batch_window(mut self, batch_window: Duration) -> Self {
+        self.batch_window = batch_window;
+        self
+    }
+
+    fn sender(&mut self) -> &Sender<LogEntry> {
+        let (sender, _) = self.worker.get_or_insert_with(|| {
+            let (sender, receiver) = mpsc::channel();
+            let agent = ureq::AgentBuilder::new().timeout(self.timeout).build();
+            let url = self.url.clone();
+            let batch_window = self.batch_window;
+            let worker = thread::Builder::new()
+                .name(String::from("simple_logger-webhook"))
+                .spawn(move || run(agent, url, batch_window, receiver))
+                .expect("failed to spawn the webhook thread");
+            (sender, worker)
+        });
+        sender
+    }
+}
+
+impl Sink for WebhookSink {
+    fn write_entry(&mut self, entry: &LogEntry) -> io::Result<()> {
+        if entry.level >= self.threshold {
+            // Only fails if the worker panicked, and then there's nothing to report to.
+            let _ = self.sender().send(entry.clone());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for WebhookSink {
+    /// Waits for alerts that are still queued to be sent.
+    fn drop(&mut self) {
+        if let Some((sender, worker)) = self.worker.take() {
+            drop(sender);
+            let _ = worker.join();
+        }
+    }
+}
+
+fn run(agent: ureq::Agent, url: String, batch_window: Duration, receiver: Receiver<LogEntry>) {
+    while let Ok(first) = receiver.recv() {
+        let mut batch = vec![first];
+        let deadline = Instant::now() + batch_window;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match receiver.recv_timeout(remaining) {
+                Ok(entry) => batch.push(entry),
+                Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        // Fire and forget: a failed alert must not take logging down with it.
+        let _ = agent
+            .post(&url)
+            .set("Content-Type", "application/json")
+            .send_string(&payload(&batch));
+    }
+}
+
+fn payload(batch: &[LogEntry]) -> String {
+    let layout = Layout::default();
+    let text: Vec<String> = batch
+        .iter()
+        .map(|entry| format::text(entry, &layout))
+        .collect();
+    let mut body = String::from("{\"text\":");
+    format::push_json_string(&mut body, &text.join("\n"));
+    body.push_str(",\"entries\":[");
+    for (index, entry) in batch.iter().enumerate() {
+        if index > 0 {
+            body.push(',');
+        }
+        body.push_str(&format::json(entry, &layout));
+    }
+    body.push_str("]}");
+    body
+}
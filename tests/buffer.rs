@@ -0,0 +1,346 @@
+use simple_logger::{LogLevel, Logger};
+use std::fs;
+
+#[test]
+fn clear_discards_entries_without_writing() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cleared.log");
+    let mut logger = Logger::builder().file_path(path.clone()).build();
+
+    assert!(logger.is_empty());
+    logger.debug("begin transaction");
+    logger.debug("update row");
+    assert_eq!(logger.len(), 2);
+    assert!(!logger.is_empty());
+
+    logger.clear();
+    assert_eq!(logger.len(), 0);
+    assert!(logger.is_empty());
+
+    logger.flush().unwrap();
+    assert!(!path.exists());
+}
+
+#[test]
+fn retain_filters_entries_before_flush() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("retained.log");
+    let mut logger = Logger::builder().file_path(path.clone()).build();
+
+    logger.info("request received");
+    logger.error("database unreachable");
+    logger.warn("retrying");
+    logger.error("giving up");
+
+    logger.retain(|entry| entry.level() == LogLevel::Error);
+    assert_eq!(logger.len(), 2);
+    logger.flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("ERROR: database unreachable"));
+    assert!(lines[1].ends_with("ERROR: giving up"));
+}
+
+#[test]
+fn stats_count_buffered_and_lifetime_entries() {
+    let mut logger = Logger::builder().build();
+    logger.warn("slow query");
+    logger.error("query failed");
+    logger.error("retry failed");
+    logger.info("giving up");
+
+    let stats = logger.stats();
+    assert_eq!(stats.error, 2);
+    assert_eq!(stats.get(LogLevel::Warn), 1);
+    assert_eq!(stats.info, 1);
+    assert_eq!(stats.debug, 0);
+    assert_eq!(stats.total(), 4);
+
+    logger.clear();
+    logger.error("another failure");
+    assert_eq!(logger.stats().total(), 1);
+    assert_eq!(logger.lifetime_stats().error, 3);
+    assert_eq!(logger.lifetime_stats().total(), 5);
+}
+
+#[test]
+fn long_messages_are_truncated_at_log_time() {
+    let mut logger = Logger::builder().build().with_max_message_len(5);
+    logger.info("short");
+    logger.info("longer message");
+    // "é" is two bytes, so the cut at byte 5 would land inside the third one.
+    logger.info("ééé");
+
+    let messages: Vec<&str> = logger.iter().map(|entry| entry.message()).collect();
+    assert_eq!(messages, ["short", "longe…[truncated]", "éé…[truncated]"]);
+}
+
+#[test]
+fn zero_max_message_len_keeps_messages_whole() {
+    let mut logger = Logger::builder().build().with_max_message_len(0);
+    let message = "x".repeat(10_000);
+    logger.info(&message);
+    assert_eq!(logger.iter().next().unwrap().message(), message);
+}
+
+#[test]
+fn rate_limit_drops_excess_entries_and_reports_them() {
+    use chrono::{TimeDelta, TimeZone, Utc};
+    use simple_logger::LogEntry;
+
+    let mut logger = Logger::builder().build().with_rate_limit(LogLevel::Warn, 2);
+    let start = Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+    let at = |millis| start + TimeDelta::milliseconds(millis);
+
+    for millis in [0, 100, 200, 300, 900] {
+        logger.push(LogEntry::new(at(millis), LogLevel::Warn, "disk slow"));
+    }
+    logger.push(LogEntry::new(at(500), LogLevel::Info, "still serving"));
+    logger.push(LogEntry::new(at(1000), LogLevel::Warn, "disk slow"));
+
+    let messages: Vec<&str> = logger.iter().map(|entry| entry.message()).collect();
+    assert_eq!(
+        messages,
+        [
+            "disk slow",
+            "disk slow",
+            "still serving",
+            "suppressed 3 WARN entries over the rate limit",
+            "disk slow",
+        ]
+    );
+}
+
+#[test]
+fn tagged_entries_keep_their_tag() {
+    let mut logger = Logger::builder().build();
+    logger.info_tagged("db", "query ran");
+    logger.info("untagged");
+
+    let tags: Vec<Option<&str>> = logger.iter().map(|entry| entry.tag()).collect();
+    assert_eq!(tags, [Some("db"), None]);
+    logger.clear();
+}
+
+#[test]
+fn tag_filter_keeps_selected_tags() {
+    use std::collections::HashSet;
+
+    let log_everything = |logger: &mut Logger| {
+        logger.info_tagged("db", "query ran");
+        logger.warn_tagged("http", "slow request");
+        logger.error_tagged("cache", "miss storm");
+        logger.info("untagged");
+        let mut buffer = Vec::new();
+        logger.flush_to_writer(&mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    };
+    let tags: HashSet<String> = ["db", "http"].map(String::from).into();
+
+    let mut logger = Logger::builder()
+        .build()
+        .with_timestamp_format(String::from("ts"))
+        .with_tag_filter(tags.clone());
+    assert_eq!(
+        log_everything(&mut logger),
+        "[ts] [db] INFO: query ran\n[ts] [http] WARN: slow request\n[ts] INFO: untagged\n"
+    );
+
+    let mut logger = Logger::builder()
+        .build()
+        .with_timestamp_format(String::from("ts"))
+        .with_tag_filter(tags)
+        .with_only_tagged(true);
+    assert_eq!(
+        log_everything(&mut logger),
+        "[ts] [db] INFO: query ran\n[ts] [http] WARN: slow request\n"
+    );
+}
+
+#[test]
+fn log_if_only_records_when_the_condition_holds() {
+    let mut logger = Logger::builder().build();
+    logger.log_if(true, LogLevel::Debug, "cache miss");
+    logger.log_if(false, LogLevel::Debug, "cache hit");
+    logger.info_if(true, "retrying");
+    logger.error_if(false, "gave up");
+
+    let messages: Vec<&str> = logger.iter().map(|entry| entry.message()).collect();
+    assert_eq!(messages, ["cache miss", "retrying"]);
+    logger.clear();
+}
+
+#[test]
+fn log_if_with_skips_the_closure_when_false() {
+    let mut logger = Logger::builder().level(LogLevel::Info).build();
+    let mut calls = 0;
+
+    logger.log_if_with(false, LogLevel::Error, || {
+        calls += 1;
+        "never built"
+    });
+    // Below the threshold, so not built either.
+    logger.log_if_with(true, LogLevel::Debug, || {
+        calls += 1;
+        "too verbose"
+    });
+    assert_eq!(calls, 0);
+    assert!(logger.is_empty());
+
+    logger.log_if_with(true, LogLevel::Warn, || format!("queue at {}%", 90));
+    assert_eq!(logger.iter().next().unwrap().message(), "queue at 90%");
+    logger.clear();
+}
+
+#[test]
+fn drain_hands_over_the_buffered_entries() {
+    let mut logger = Logger::builder().build();
+    logger.info("request started");
+    logger.error("request failed");
+
+    let drained = logger.drain();
+    let levels: Vec<LogLevel> = drained.iter().map(|entry| entry.level()).collect();
+    let messages: Vec<&str> = drained.iter().map(|entry| entry.message()).collect();
+    assert_eq!(levels, [LogLevel::Info, LogLevel::Error]);
+    assert_eq!(messages, ["request started", "request failed"]);
+    assert!(logger.is_empty());
+    assert!(logger.drain().is_empty());
+}
+
+#[test]
+fn append_merges_loggers_in_time_order() {
+    use chrono::DateTime;
+    use simple_logger::LogEntry;
+
+    let at = |time: &str, message: &'static str| {
+        let timestamp = DateTime::parse_from_rfc3339(&format!("2024-06-01T{time}+00:00")).unwrap();
+        LogEntry::new(timestamp, LogLevel::Info, message)
+    };
+    let mut main = Logger::builder()
+        .build()
+        .with_timestamp_format(String::from("%H:%M:%S"));
+    main.extend([at("10:00:00", "main started"), at("10:00:02", "main ready")]);
+    let mut worker = Logger::builder().build();
+    worker.extend([
+        at("10:00:01", "worker started"),
+        at("10:00:03", "worker done"),
+    ]);
+
+    main.append(&mut worker);
+    assert!(worker.is_empty());
+
+    let mut buffer = Vec::new();
+    main.flush_to_writer(&mut buffer).unwrap();
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "[10:00:00] INFO: main started\n\
+         [10:00:01] INFO: worker started\n\
+         [10:00:02] INFO: main ready\n\
+         [10:00:03] INFO: worker done\n"
+    );
+}
+
+#[test]
+fn writing_to_the_logger_buffers_lines() {
+    use std::io::Write;
+
+    let mut logger = Logger::builder().build();
+    writeln!(logger, "hello {}", 42).unwrap();
+    logger.write_all(b"first line\nsecond line\n").unwrap();
+
+    let mut logger = logger.with_write_level(LogLevel::Warn);
+    writeln!(logger, "careful").unwrap();
+
+    let entries: Vec<(LogLevel, &str)> = logger
+        .iter()
+        .map(|entry| (entry.level(), entry.message()))
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            (LogLevel::Info, "hello 42"),
+            (LogLevel::Info, "first line"),
+            (LogLevel::Info, "second line"),
+            (LogLevel::Warn, "careful"),
+        ]
+    );
+    logger.clear();
+}
+
+#[test]
+fn with_capacity_preallocates_the_buffer() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut logger = Logger::with_capacity(dir.path().join("app.log"), 1000);
+    assert!(logger.capacity() >= 1000);
+
+    logger.info("first");
+    assert!(logger.capacity() >= 1000);
+    logger.clear();
+}
+
+#[test]
+fn last_returns_the_most_recent_entry() {
+    let mut logger = Logger::builder().build();
+    assert!(logger.last().is_none());
+
+    logger.info("connected");
+    logger.warn("slow response");
+    logger.error("connection lost");
+
+    let last = logger.last().unwrap();
+    assert_eq!(last.level(), LogLevel::Error);
+    assert_eq!(last.message(), "connection lost");
+    assert_eq!(logger.len(), 3);
+    logger.clear();
+}
+
+#[test]
+fn sampling_keeps_one_in_n_entries() {
+    let mut logger = Logger::builder()
+        .build()
+        .with_sampling(LogLevel::Debug, 10)
+        .with_sampling(LogLevel::Error, 10);
+
+    for i in 0..100 {
+        logger.debug(format!("tick {i}"));
+    }
+    logger.error("failed");
+    logger.error("failed again");
+
+    let stats = logger.stats();
+    assert_eq!(stats.debug, 10);
+    assert_eq!(stats.error, 2);
+    assert_eq!(logger.iter().nth(1).unwrap().message(), "tick 10");
+    logger.clear();
+}
+
+#[test]
+fn borrowed_messages_outlive_their_source() {
+    let mut logger = Logger::builder().build();
+    let owned = String::from("short-lived");
+    let borrowed: &str = owned.as_str();
+    logger.info(borrowed);
+    drop(owned);
+    assert_eq!(logger.iter().next().unwrap().message(), "short-lived");
+    logger.clear();
+}
+
+#[test]
+fn lines_written_in_pieces_become_one_entry() {
+    use std::io::Write;
+
+    let mut logger = Logger::builder().build();
+    logger.write_all(b"partial ").unwrap();
+    write!(logger, "line {}", 1).unwrap();
+    assert!(logger.is_empty());
+    logger.write_all(b"\nsecond").unwrap();
+
+    let messages: Vec<&str> = logger.iter().map(|entry| entry.message()).collect();
+    assert_eq!(messages, ["partial line 1"]);
+
+    logger.write_all(b" line\n").unwrap();
+    assert_eq!(logger.last().unwrap().message(), "second line");
+    logger.clear();
+}
@@ -0,0 +1,39 @@
+#![cfg(feature = "compression")]
+// The `&format!(..)` call site predates owned messages, and checks it still compiles.
+#![allow(clippy::needless_borrows_for_generic_args)]
+
+use flate2::read::GzDecoder;
+use simple_logger::Logger;
+use std::fs::{self, File};
+use std::io::Read;
+
+#[test]
+fn rotated_backups_are_gzipped() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.log");
+    // Every line is ~36 bytes, so each file holds a single entry.
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .max_file_size(50)
+        .max_backups(2)
+        .compress_backups(true)
+        .build();
+
+    for i in 1..=4 {
+        logger.info(&format!("entry {i}"));
+        logger.flush().unwrap();
+    }
+
+    let gunzip = |name: &str| {
+        let mut contents = String::new();
+        GzDecoder::new(File::open(dir.path().join(name)).unwrap())
+            .read_to_string(&mut contents)
+            .unwrap();
+        contents
+    };
+    assert!(fs::read_to_string(&path).unwrap().ends_with("entry 4\n"));
+    assert!(gunzip("app.log.1.gz").ends_with("entry 3\n"));
+    assert!(gunzip("app.log.2.gz").ends_with("entry 2\n"));
+    assert!(!dir.path().join("app.log.1").exists());
+    assert!(!dir.path().join("app.log.3.gz").exists());
+}
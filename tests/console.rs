@@ -0,0 +1,157 @@
+use simple_logger::{ConsoleSink, LogLevel, Logger};
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl Capture {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn log_to_console(color: bool) -> (String, String) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("console.log");
+    let capture = Capture::default();
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .build()
+        .with_console(ConsoleSink::writer(capture.clone()).with_color(color));
+
+    logger.error("boom");
+    logger.info("fine");
+    logger.flush().unwrap();
+
+    (capture.contents(), fs::read_to_string(&path).unwrap())
+}
+
+#[test]
+fn colors_only_appear_on_the_console_when_enabled() {
+    let _guard = ENV_LOCK.lock().unwrap();
+    env::remove_var("NO_COLOR");
+
+    let (console, file) = log_to_console(true);
+    assert!(console.contains("\x1b[31m"));
+    assert!(console.contains("\x1b[32m"));
+    assert!(!file.contains('\x1b'));
+
+    let (console, file) = log_to_console(false);
+    assert!(console.contains("ERROR: boom"));
+    assert!(!console.contains('\x1b'));
+    assert!(!file.contains('\x1b'));
+}
+
+#[test]
+fn no_color_disables_colors() {
+    let _guard = ENV_LOCK.lock().unwrap();
+    env::set_var("NO_COLOR", "1");
+
+    let (console, _) = log_to_console(true);
+    env::remove_var("NO_COLOR");
+    assert!(console.contains("ERROR: boom"));
+    assert!(!console.contains('\x1b'));
+}
+
+#[test]
+fn stderr_threshold_splits_console_streams() {
+    let out = Capture::default();
+    let err = Capture::default();
+    let mut logger = Logger::builder()
+        .build()
+        .with_console(ConsoleSink::writers(out.clone(), err.clone()))
+        .with_stderr_threshold(LogLevel::Warn);
+
+    logger.info("starting");
+    logger.warn("slow");
+    logger.debug("tick");
+    logger.error("failed");
+    logger.flush_to_writer(&mut io::sink()).unwrap();
+
+    let out = out.contents();
+    let err = err.contents();
+    let out: Vec<&str> = out.lines().collect();
+    let err: Vec<&str> = err.lines().collect();
+    assert_eq!(out.len(), 2);
+    assert!(out[0].ends_with("INFO: starting"));
+    assert!(out[1].ends_with("DEBUG: tick"));
+    assert_eq!(err.len(), 2);
+    assert!(err[0].ends_with("WARN: slow"));
+    assert!(err[1].ends_with("ERROR: failed"));
+}
+
+#[test]
+fn control_characters_are_escaped() {
+    let capture = Capture::default();
+    let mut logger = Logger::builder()
+        .build()
+        .with_timestamp_format(String::from("ts"))
+        .with_console(ConsoleSink::writer(capture.clone()));
+    let spoof = "login ok\x1b[2K\r\n[ts] INFO: admin logged in";
+
+    logger.info(spoof);
+    let mut raw = Vec::new();
+    logger.flush_to_writer(&mut raw).unwrap();
+    assert!(capture
+        .contents()
+        .ends_with("INFO: login ok\\x1b[2K\\r\\n[ts] INFO: admin logged in\n"));
+    assert_eq!(String::from_utf8(raw).unwrap().lines().count(), 2);
+
+    let mut logger = logger.with_sanitize(true);
+    logger.info(spoof);
+    let mut sanitized = Vec::new();
+    logger.flush_to_writer(&mut sanitized).unwrap();
+    assert_eq!(
+        String::from_utf8(sanitized).unwrap(),
+        "[ts] INFO: login ok\\x1b[2K\\r\\n[ts] INFO: admin logged in\n"
+    );
+}
+
+const COLOR_ENV: &str = "SIMPLE_LOGGER_COLOR_TEST";
+
+#[test]
+fn with_color_false_leaves_the_console_off() {
+    if let Some(color) = env::var_os(COLOR_ENV) {
+        let mut logger = Logger::builder()
+            .build()
+            .with_fallback_paths([])
+            .with_color(color == "1");
+        logger.error("echoed");
+        logger.clear();
+        return;
+    }
+
+    let stderr = |color: &str| {
+        let output = Command::new(env::current_exe().unwrap())
+            .args([
+                "--exact",
+                "with_color_false_leaves_the_console_off",
+                "--nocapture",
+            ])
+            .env(COLOR_ENV, color)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stderr).unwrap()
+    };
+
+    assert!(stderr("1").contains("ERROR: echoed"));
+    assert!(!stderr("0").contains("echoed"));
+}
@@ -0,0 +1,102 @@
+use simple_logger::{default_log_path, LogLevel, Logger};
+use std::env;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+#[test]
+fn from_env_reads_log_file_and_level() {
+    let _guard = ENV_LOCK.lock().unwrap();
+    env::set_var("LOG_FILE", "/tmp/from-env/app.log");
+    env::set_var("LOG_LEVEL", "warn");
+
+    let logger = Logger::from_env();
+    env::remove_var("LOG_FILE");
+    env::remove_var("LOG_LEVEL");
+
+    assert_eq!(logger.file_path(), PathBuf::from("/tmp/from-env/app.log"));
+    assert!(!logger.enabled(LogLevel::Info));
+    assert!(logger.enabled(LogLevel::Warn));
+}
+
+#[test]
+fn from_env_falls_back_to_defaults() {
+    let _guard = ENV_LOCK.lock().unwrap();
+    env::remove_var("LOG_FILE");
+    env::remove_var("LOG_LEVEL");
+
+    let logger = Logger::from_env();
+    assert_eq!(logger.file_path(), default_log_path());
+    assert!(logger.enabled(LogLevel::Trace));
+}
+
+#[test]
+fn custom_level_env_var_is_read_instead_of_log_level() {
+    let _guard = ENV_LOCK.lock().unwrap();
+    env::set_var("LOG_LEVEL", "trace");
+    env::set_var("MYAPP_LOG_LEVEL", "error");
+
+    let logger = Logger::new(PathBuf::from("unused.log"))
+        .with_level_env_var(String::from("MYAPP_LOG_LEVEL"));
+    assert!(!logger.enabled(LogLevel::Warn));
+    assert!(logger.enabled(LogLevel::Error));
+
+    env::remove_var("MYAPP_LOG_LEVEL");
+    assert!(logger.enabled(LogLevel::Trace));
+    env::remove_var("LOG_LEVEL");
+}
+
+#[test]
+fn default_logger_writes_to_the_default_path() {
+    let _guard = ENV_LOCK.lock().unwrap();
+    env::remove_var("LOG_LEVEL");
+
+    // Without fallbacks, so a failure to write the default location shows.
+    let mut logger = Logger::default().with_fallback_paths([]);
+    assert_eq!(logger.file_path(), default_log_path());
+    assert!(logger.is_empty());
+
+    logger.info("written by Logger::default");
+    match logger.flush() {
+        Ok(()) => {}
+        // Non-root runs usually can't write to the system log directory.
+        Err(simple_logger::LoggerError::Io(err))
+            if err.kind() == std::io::ErrorKind::PermissionDenied =>
+        {
+            return
+        }
+        Err(err) => panic!("flush failed: {err}"),
+    }
+
+    let path = default_log_path();
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(contents.contains("written by Logger::default"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn reload_level_picks_up_a_changed_variable() {
+    let _guard = ENV_LOCK.lock().unwrap();
+    env::set_var("RELOAD_LOG_LEVEL", "error");
+    let mut logger = Logger::builder()
+        .build()
+        .with_level_env_var(String::from("RELOAD_LOG_LEVEL"));
+    assert!(logger.reload_level());
+    assert!(!logger.reload_level());
+
+    env::set_var("RELOAD_LOG_LEVEL", "debug");
+    logger.debug("before reload");
+    let changed = logger.reload_level();
+    env::set_var("RELOAD_LOG_LEVEL", "loud");
+    assert!(!logger.reload_level());
+    env::remove_var("RELOAD_LOG_LEVEL");
+    assert!(changed);
+
+    logger.debug("after reload");
+    let mut buffer = Vec::new();
+    logger.flush_to_writer(&mut buffer).unwrap();
+    let output = String::from_utf8(buffer).unwrap();
+    assert_eq!(output.lines().count(), 2);
+    assert!(output.contains("DEBUG: after reload"));
+}
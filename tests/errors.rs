@@ -0,0 +1,395 @@
+use simple_logger::{LogEntry, LogLevel, Logger, LoggerError, Sink};
+use std::io::{self, Write};
+use std::process::Command;
+
+struct Broken;
+
+impl Write for Broken {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::Error::other("disk on fire"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Sink for Broken {
+    fn write_entry(&mut self, _entry: &LogEntry) -> io::Result<()> {
+        Err(io::Error::other("sink on fire"))
+    }
+}
+
+#[test]
+fn write_failures_are_io_errors() {
+    let mut logger = Logger::builder().build();
+    logger.info("lost");
+    let err = logger.flush_to_writer(&mut Broken).unwrap_err();
+    assert!(matches!(err, LoggerError::Io(ref err) if err.to_string() == "disk on fire"));
+    logger.clear();
+}
+
+#[test]
+fn routing_to_a_directory_is_reported() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut logger = Logger::builder()
+        .file_path(dir.path().join("app.log"))
+        .build()
+        .with_level_routing([(LogLevel::Error, dir.path().to_path_buf())]);
+    logger.error("nowhere to go");
+
+    match logger.flush() {
+        Err(LoggerError::IsDirectory(path)) => assert_eq!(path, dir.path()),
+        other => panic!("expected IsDirectory, got {other:?}"),
+    }
+    logger.clear();
+}
+
+#[test]
+fn sink_failures_are_sink_errors() {
+    let mut logger = Logger::builder().build();
+    logger.add_sink(Broken);
+    logger.info("echoed");
+    assert!(matches!(
+        logger.take_last_error(),
+        Some(LoggerError::Sink(_))
+    ));
+    logger.clear();
+}
+
+#[test]
+fn level_parse_errors_convert_with_question_mark() {
+    fn configured_level(name: &str) -> Result<LogLevel, LoggerError> {
+        Ok(name.parse()?)
+    }
+
+    assert_eq!(configured_level("warn").unwrap(), LogLevel::Warn);
+    assert!(matches!(
+        configured_level("loud"),
+        Err(LoggerError::InvalidLevel(_))
+    ));
+}
+
+#[test]
+fn unwritable_log_file_falls_back() {
+    let dir = tempfile::tempdir().unwrap();
+    let blocker = dir.path().join("not-a-dir");
+    std::fs::write(&blocker, "").unwrap();
+    let fallback = dir.path().join("fallback");
+    std::fs::create_dir(&fallback).unwrap();
+
+    let mut logger = Logger::builder()
+        .file_path(blocker.join("app.log"))
+        .build()
+        .with_fallback_paths([blocker.join("logs/"), fallback.clone()]);
+    logger.info("still recorded");
+    logger.flush().unwrap();
+    logger.info("recorded later");
+    logger.flush().unwrap();
+
+    let contents = std::fs::read_to_string(fallback.join("app.log")).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].ends_with("INFO: still recorded"));
+    assert!(lines[1].contains("WARN: could not open "));
+    assert!(lines[1].ends_with(&format!(
+        "logging to {} instead",
+        fallback.join("app.log").display()
+    )));
+    assert!(lines[2].ends_with("INFO: recorded later"));
+}
+
+const FALLBACK_DIR_ENV: &str = "SIMPLE_LOGGER_FALLBACK_TEST_DIR";
+
+#[test]
+fn fallback_is_announced_on_stderr_whatever_the_level() {
+    if let Some(dir) = std::env::var_os(FALLBACK_DIR_ENV) {
+        let dir = std::path::PathBuf::from(dir);
+        let mut logger = Logger::builder()
+            .file_path(dir.join("not-a-dir/app.log"))
+            .level(LogLevel::Error)
+            .build()
+            .with_fallback_paths([dir.join("fallback.log")]);
+        logger.error("still recorded");
+        logger.flush().unwrap();
+        return;
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("not-a-dir"), "").unwrap();
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args([
+            "--exact",
+            "fallback_is_announced_on_stderr_whatever_the_level",
+            "--nocapture",
+        ])
+        .env(FALLBACK_DIR_ENV, dir.path())
+        .env_remove("SIMPLE_LOGGER_QUIET")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("simple_logger: could not open "),
+        "{stderr}"
+    );
+    assert!(stderr.contains(&format!(
+        "logging to {} instead",
+        dir.path().join("fallback.log").display()
+    )));
+
+    // The warning entry itself is below the level, so only the error is written.
+    let contents = std::fs::read_to_string(dir.path().join("fallback.log")).unwrap();
+    assert_eq!(contents.lines().count(), 1);
+}
+
+#[test]
+fn without_fallbacks_is_the_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let blocker = dir.path().join("not-a-dir");
+    std::fs::write(&blocker, "").unwrap();
+
+    let mut logger = Logger::builder().file_path(blocker.join("app.log")).build();
+    logger.info("nowhere to go");
+    assert!(matches!(logger.flush(), Err(LoggerError::Io(_))));
+    logger.clear();
+}
+
+#[test]
+fn without_fallbacks_flush_reports_the_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let blocker = dir.path().join("not-a-dir");
+    std::fs::write(&blocker, "").unwrap();
+
+    let mut logger = Logger::builder()
+        .file_path(blocker.join("app.log"))
+        .build()
+        .with_fallback_paths([]);
+    logger.info("nowhere to go");
+    assert!(matches!(logger.flush(), Err(LoggerError::Io(_))));
+    logger.clear();
+}
+
+const DROP_PATH_ENV: &str = "SIMPLE_LOGGER_DROP_TEST_PATH";
+
+#[test]
+fn failed_flush_on_drop_is_reported() {
+    if let Some(path) = std::env::var_os(DROP_PATH_ENV) {
+        let mut logger = Logger::builder()
+            .file_path(path.into())
+            .level(LogLevel::Info)
+            .build()
+            .with_fallback_paths([]);
+        logger.info("first");
+        logger.debug("filtered out, so never lost");
+        logger.info("second");
+        return;
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let blocker = dir.path().join("not-a-dir");
+    std::fs::write(&blocker, "").unwrap();
+    let run = |path: &std::path::Path, quiet: bool| {
+        let mut command = Command::new(std::env::current_exe().unwrap());
+        command
+            .args(["--exact", "failed_flush_on_drop_is_reported", "--nocapture"])
+            .env(DROP_PATH_ENV, path)
+            .env_remove("SIMPLE_LOGGER_QUIET");
+        if quiet {
+            command.env("SIMPLE_LOGGER_QUIET", "1");
+        }
+        let output = command.output().unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stderr).unwrap()
+    };
+
+    // One file can't be opened, the other opens but refuses every write.
+    let unopenable = blocker.join("app.log");
+    let mut paths = vec![unopenable.as_path()];
+    if cfg!(target_os = "linux") {
+        paths.push(std::path::Path::new("/dev/full"));
+    }
+    for path in paths {
+        let stderr = run(path, false);
+        assert!(
+            stderr.contains("simple_logger: lost 2 log entries on drop: "),
+            "{path:?}: {stderr}"
+        );
+        assert!(!run(path, true).contains("simple_logger:"));
+    }
+}
+
+#[test]
+fn flush_retries_transient_write_failures() {
+    struct Flaky {
+        failures: usize,
+        written: Vec<u8>,
+    }
+
+    impl Write for Flaky {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err(io::Error::other("disk busy"));
+            }
+            self.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut logger = Logger::builder()
+        .build()
+        .with_timestamp_format(String::from("ts"))
+        .with_flush_retries(2, std::time::Duration::from_millis(1));
+    logger.info("first");
+    logger.info("second");
+    let mut writer = Flaky {
+        failures: 1,
+        written: Vec::new(),
+    };
+    logger.flush_to_writer(&mut writer).unwrap();
+    assert_eq!(
+        String::from_utf8(writer.written).unwrap(),
+        "[ts] INFO: first\n[ts] INFO: second\n"
+    );
+
+    logger.info("third");
+    let mut writer = Flaky {
+        failures: 3,
+        written: Vec::new(),
+    };
+    assert!(logger.flush_to_writer(&mut writer).is_err());
+    logger.clear();
+}
+
+#[test]
+fn failed_flush_never_writes_a_line_twice() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.log");
+    let blocked = dir.path().join("errors");
+    std::fs::write(&blocked, "").unwrap();
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .build()
+        .with_level_routing([(LogLevel::Error, blocked.join("app.error.log"))]);
+
+    // The first line is handed to app.log before the route fails to open.
+    logger.info("first");
+    logger.error("second");
+    assert!(logger.flush().is_err());
+
+    std::fs::remove_file(&blocked).unwrap();
+    logger.flush().unwrap();
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(contents.matches("INFO: first").count(), 1);
+    let errors = std::fs::read_to_string(blocked.join("app.error.log")).unwrap();
+    assert_eq!(errors.matches("ERROR: second").count(), 1);
+}
+
+#[test]
+fn failed_write_keeps_only_the_unwritten_lines() {
+    struct FailsOnce {
+        calls: usize,
+        written: Vec<u8>,
+    }
+
+    impl Write for FailsOnce {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.calls += 1;
+            match self.calls {
+                // Takes the first line only, then fails.
+                1 => {
+                    let end = buf.iter().position(|&byte| byte == b'\n').unwrap() + 1;
+                    self.written.extend_from_slice(&buf[..end]);
+                    Ok(end)
+                }
+                2 => Err(io::Error::other("disk busy")),
+                _ => {
+                    self.written.extend_from_slice(buf);
+                    Ok(buf.len())
+                }
+            }
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut logger = Logger::builder()
+        .build()
+        .with_timestamp_format(String::from("ts"));
+    logger.info("first");
+    logger.info("second");
+    let mut writer = FailsOnce {
+        calls: 0,
+        written: Vec::new(),
+    };
+    assert!(logger.flush_to_writer(&mut writer).is_err());
+    logger.flush_to_writer(&mut writer).unwrap();
+    assert_eq!(
+        String::from_utf8(writer.written).unwrap(),
+        "[ts] INFO: first\n[ts] INFO: second\n"
+    );
+}
+
+#[test]
+fn unbatched_retries_write_each_line_once() {
+    struct FailsFirst {
+        failed: bool,
+        written: Vec<u8>,
+    }
+
+    impl Write for FailsFirst {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if !self.failed {
+                self.failed = true;
+                return Err(io::Error::other("disk busy"));
+            }
+            self.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut logger = Logger::builder()
+        .build()
+        .with_timestamp_format(String::from("ts"))
+        .with_batched_writes(false)
+        .with_flush_retries(1, std::time::Duration::from_millis(1));
+    logger.info("first");
+    logger.info("second");
+    let mut writer = FailsFirst {
+        failed: false,
+        written: Vec::new(),
+    };
+    logger.flush_to_writer(&mut writer).unwrap();
+    assert_eq!(
+        String::from_utf8(writer.written).unwrap(),
+        "[ts] INFO: first\n[ts] INFO: second\n"
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn lines_a_full_disk_refused_stay_buffered() {
+    let mut logger = Logger::builder()
+        .file_path("/dev/full".into())
+        .build()
+        .with_fallback_paths([]);
+    logger.info("first");
+    logger.info("second");
+
+    assert!(logger.flush().is_err());
+    assert_eq!(logger.len(), 2);
+    assert!(logger.flush().is_err());
+    assert!(!logger.is_flushed());
+    logger.clear();
+}
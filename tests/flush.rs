@@ -0,0 +1,522 @@
+// The `&format!(..)` call sites predate owned messages, and check they still compile.
+#![allow(clippy::needless_borrows_for_generic_args)]
+
+use simple_logger::Logger;
+use std::env;
+use std::fs;
+use std::sync::Mutex;
+
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+#[test]
+fn flushing_twice_appends_to_existing_file() {
+    let _guard = ENV_LOCK.lock().unwrap();
+    env::set_var("LOG_LEVEL", "debug");
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.log");
+
+    let mut first = Logger::new(path.clone());
+    first.info("first run");
+    first.flush().unwrap();
+
+    let mut second = Logger::new(path.clone());
+    second.error("second run");
+    second.flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    assert!(contents.contains("first run"));
+    assert!(contents.contains("second run"));
+    assert_eq!(contents.lines().count(), 2);
+}
+
+#[test]
+fn warnings_are_excluded_from_error_output() {
+    let _guard = ENV_LOCK.lock().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    env::set_var("LOG_LEVEL", "default");
+    let default_path = dir.path().join("default.log");
+    let mut logger = Logger::new(default_path.clone());
+    logger.warn("disk almost full");
+    logger.flush().unwrap();
+    let contents = fs::read_to_string(&default_path).unwrap();
+    assert!(contents.contains("WARN: disk almost full"));
+
+    env::set_var("LOG_LEVEL", "error");
+    let error_path = dir.path().join("error.log");
+    let mut logger = Logger::new(error_path.clone());
+    logger.warn("disk almost full");
+    logger.error("disk full");
+    logger.flush().unwrap();
+    let contents = fs::read_to_string(&error_path).unwrap();
+    assert!(!contents.contains("disk almost full"));
+    assert!(contents.contains("ERROR: disk full"));
+}
+
+#[test]
+fn trace_entries_only_written_at_trace_level() {
+    let _guard = ENV_LOCK.lock().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    env::set_var("LOG_LEVEL", "debug");
+    let debug_path = dir.path().join("debug.log");
+    let mut logger = Logger::new(debug_path.clone());
+    logger.trace("entering loop");
+    logger.debug("loop state");
+    logger.flush().unwrap();
+    let contents = fs::read_to_string(&debug_path).unwrap();
+    assert!(!contents.contains("entering loop"));
+    assert!(contents.contains("loop state"));
+
+    env::set_var("LOG_LEVEL", "trace");
+    let trace_path = dir.path().join("trace.log");
+    let mut logger = Logger::new(trace_path.clone());
+    logger.trace("entering loop");
+    logger.debug("loop state");
+    logger.info("loop done");
+    logger.flush().unwrap();
+    let contents = fs::read_to_string(&trace_path).unwrap();
+    assert!(contents.contains("TRACE: entering loop"));
+    assert!(contents.contains("DEBUG: loop state"));
+    assert!(contents.contains("INFO: loop done"));
+}
+
+fn flush_all_levels(path: std::path::PathBuf) -> String {
+    let mut logger = Logger::new(path.clone());
+    logger.debug("debug entry");
+    logger.info("info entry");
+    logger.error("error entry");
+    logger.flush().unwrap();
+    fs::read_to_string(&path).unwrap()
+}
+
+#[test]
+fn unset_log_level_falls_back_to_default() {
+    let _guard = ENV_LOCK.lock().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    env::remove_var("LOG_LEVEL");
+    let contents = flush_all_levels(dir.path().join("unset.log"));
+    assert_eq!(contents.lines().count(), 3);
+}
+
+#[test]
+fn empty_log_level_falls_back_to_default() {
+    let _guard = ENV_LOCK.lock().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    env::set_var("LOG_LEVEL", "");
+    let contents = flush_all_levels(dir.path().join("empty.log"));
+    assert_eq!(contents.lines().count(), 3);
+}
+
+#[test]
+fn unknown_log_level_falls_back_to_default() {
+    let _guard = ENV_LOCK.lock().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    env::set_var("LOG_LEVEL", "verbose");
+    let contents = flush_all_levels(dir.path().join("verbose.log"));
+    assert_eq!(contents.lines().count(), 3);
+}
+
+#[test]
+fn threshold_keeps_entries_at_or_above_level() {
+    let _guard = ENV_LOCK.lock().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    let cases: [(&str, &[&str]); 6] = [
+        ("default", &["TRACE", "DEBUG", "INFO", "WARN", "ERROR"]),
+        ("trace", &["TRACE", "DEBUG", "INFO", "WARN", "ERROR"]),
+        ("debug", &["DEBUG", "INFO", "WARN", "ERROR"]),
+        ("info", &["INFO", "WARN", "ERROR"]),
+        ("warn", &["WARN", "ERROR"]),
+        ("error", &["ERROR"]),
+    ];
+
+    for (level, expected) in cases {
+        env::set_var("LOG_LEVEL", level);
+        let path = dir.path().join(format!("{level}.log"));
+        let mut logger = Logger::new(path.clone());
+        logger.trace("message");
+        logger.debug("message");
+        logger.info("message");
+        logger.warn("message");
+        logger.error("message");
+        logger.flush().unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let written: Vec<&str> = contents
+            .lines()
+            .map(|line| line.split("] ").nth(1).unwrap().split(':').next().unwrap())
+            .collect();
+        assert_eq!(written, expected, "LOG_LEVEL={level}");
+    }
+}
+
+#[test]
+fn flush_keeps_logger_usable_without_duplicates() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("service.log");
+    let mut logger = Logger::builder().file_path(path.clone()).build();
+
+    logger.info("first batch");
+    logger.flush().unwrap();
+    logger.info("second batch");
+    logger.flush().unwrap();
+    logger.flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("first batch"));
+    assert!(lines[1].ends_with("second batch"));
+}
+
+#[test]
+fn dropping_logger_flushes_pending_entries() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("dropped.log");
+
+    {
+        let mut logger = Logger::builder().file_path(path.clone()).build();
+        logger.info("request handled");
+        logger.error("request failed");
+    }
+
+    let contents = fs::read_to_string(&path).unwrap();
+    assert!(contents.contains("request handled"));
+    assert!(contents.contains("request failed"));
+}
+
+#[test]
+fn flush_to_writer_renders_into_memory() {
+    let mut logger = Logger::builder()
+        .level(simple_logger::LogLevel::Info)
+        .build()
+        .with_timestamp_format(String::from("ts"));
+    logger.debug("hidden");
+    logger.info("shown");
+    logger.error("also shown");
+
+    let mut buffer = Vec::new();
+    logger.flush_to_writer(&mut buffer).unwrap();
+
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "[ts] INFO: shown\n[ts] ERROR: also shown\n"
+    );
+}
+
+#[test]
+fn reaching_max_buffered_flushes_automatically() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("capped.log");
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .build()
+        .with_max_buffered(3);
+
+    for i in 0..4 {
+        logger.info(&format!("entry {i}"));
+    }
+    assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 3);
+    assert!(logger.take_last_error().is_none());
+
+    logger.flush().unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 4);
+}
+
+#[test]
+fn guard_flushes_at_end_of_scope() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("guarded.log");
+    let mut logger = Logger::builder().file_path(path.clone()).build();
+
+    {
+        let mut guard = logger.guard();
+        guard.info("inside scope");
+        assert!(!path.exists());
+    }
+
+    assert!(fs::read_to_string(&path).unwrap().contains("inside scope"));
+    assert!(logger.is_empty());
+}
+
+#[test]
+fn flush_with_sync_succeeds() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("synced.log");
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .build()
+        .with_sync(true);
+
+    logger.error("must survive a crash");
+    logger.flush().unwrap();
+
+    assert!(fs::read_to_string(&path)
+        .unwrap()
+        .contains("must survive a crash"));
+}
+
+#[test]
+fn level_routing_splits_entries_across_files() {
+    use simple_logger::LogLevel;
+
+    let dir = tempfile::tempdir().unwrap();
+    let main_path = dir.path().join("app.log");
+    let error_path = dir.path().join("app.error.log");
+    let mut logger = Logger::builder()
+        .file_path(main_path.clone())
+        .build()
+        .with_level_routing([
+            (LogLevel::Warn, error_path.clone()),
+            (LogLevel::Error, error_path.clone()),
+        ]);
+
+    logger.info("started");
+    logger.warn("slow");
+    logger.error("failed");
+    logger.info("stopped");
+    logger.flush().unwrap();
+
+    let main = fs::read_to_string(&main_path).unwrap();
+    let errors = fs::read_to_string(&error_path).unwrap();
+    assert_eq!(main.lines().count(), 2);
+    assert!(main.contains("INFO: started") && main.contains("INFO: stopped"));
+    let errors: Vec<&str> = errors.lines().collect();
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].ends_with("WARN: slow"));
+    assert!(errors[1].ends_with("ERROR: failed"));
+}
+
+#[test]
+fn sorted_flush_writes_entries_in_time_order() {
+    use chrono::DateTime;
+    use simple_logger::{LogEntry, LogLevel};
+
+    let at = |time: &str, message: &'static str| {
+        let timestamp = DateTime::parse_from_rfc3339(&format!("2024-06-01T{time}+00:00")).unwrap();
+        LogEntry::new(timestamp, LogLevel::Info, message)
+    };
+    let mut logger = Logger::builder()
+        .build()
+        .with_timestamp_format(String::from("%H:%M:%S"))
+        .with_sorted_flush(true);
+    logger.extend([
+        at("10:00:02", "third"),
+        at("10:00:00", "first"),
+        at("10:00:01", "second, logged first"),
+        at("10:00:01", "second, logged last"),
+    ]);
+
+    let mut buffer = Vec::new();
+    logger.flush_to_writer(&mut buffer).unwrap();
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "[10:00:00] INFO: first\n\
+         [10:00:01] INFO: second, logged first\n\
+         [10:00:01] INFO: second, logged last\n\
+         [10:00:02] INFO: third\n"
+    );
+}
+
+#[test]
+fn finalize_appends_a_summary_footer() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("run.log");
+    let mut logger = Logger::builder().file_path(path.clone()).build();
+
+    logger.debug("config loaded");
+    logger.info("started");
+    logger.flush().unwrap();
+    logger.info("working");
+    logger.warn("slow");
+    logger.error("failed");
+    logger.finalize().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 6);
+    assert!(lines[4].ends_with("ERROR: failed"));
+    let footer = lines[5]
+        .strip_prefix(
+            "# session ended: 5 entries (default=0 trace=0 debug=1 info=2 warn=1 error=1) over ",
+        )
+        .unwrap();
+    assert!(footer.strip_suffix('s').unwrap().parse::<f64>().is_ok());
+}
+
+#[test]
+fn batched_and_per_entry_writes_produce_the_same_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let write = |name: &str, batch: bool| {
+        let path = dir.path().join(name);
+        let mut logger = Logger::builder()
+            .file_path(path.clone())
+            .build()
+            .with_timestamp_format(String::from("ts"))
+            .with_batched_writes(batch);
+        for i in 0..100 {
+            logger.info(format!("entry {i}"));
+        }
+        logger.flush().unwrap();
+        fs::read_to_string(path).unwrap()
+    };
+
+    let batched = write("batched.log", true);
+    assert_eq!(batched.lines().count(), 100);
+    assert_eq!(batched, write("per-entry.log", false));
+}
+
+#[test]
+fn is_flushed_tracks_pending_entries() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut logger = Logger::builder()
+        .file_path(dir.path().join("app.log"))
+        .build();
+    assert!(logger.is_flushed());
+
+    logger.info("pending");
+    assert!(!logger.is_flushed());
+    logger.flush().unwrap();
+    assert!(logger.is_flushed());
+
+    logger.info("pending again");
+    assert!(!logger.is_flushed());
+    logger.clear();
+    assert!(logger.is_flushed());
+}
+
+#[test]
+fn flush_between_writes_only_the_window() {
+    use chrono::{DateTime, FixedOffset};
+    use simple_logger::{LogEntry, LogLevel};
+
+    let time = |time: &str| -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(&format!("2024-06-01T{time}+00:00")).unwrap()
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("incident.log");
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .build()
+        .with_timestamp_format(String::from("%H:%M"));
+    logger.extend(
+        ["10:00", "10:01", "10:02", "10:03", "10:04"]
+            .map(|at| LogEntry::new(time(&format!("{at}:00")), LogLevel::Info, at)),
+    );
+
+    logger
+        .flush_between(time("10:01:00"), time("10:03:00"))
+        .unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        contents,
+        "[10:01] INFO: 10:01\n[10:02] INFO: 10:02\n[10:03] INFO: 10:03\n"
+    );
+    let left: Vec<&str> = logger.iter().map(|entry| entry.message()).collect();
+    assert_eq!(left, ["10:00", "10:04"]);
+    assert!(!logger.is_flushed());
+    logger.clear();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn failed_flush_between_keeps_the_buffer_in_order() {
+    use chrono::{DateTime, FixedOffset};
+    use simple_logger::{LogEntry, LogLevel};
+
+    let time = |time: &str| -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(&format!("2024-06-01T{time}+00:00")).unwrap()
+    };
+    let mut logger = Logger::builder()
+        .file_path("/dev/full".into())
+        .level(LogLevel::Info)
+        .build()
+        .with_fallback_paths([])
+        .with_timestamp_format(String::from("%H:%M"));
+    // The window's entries aren't next to each other in the buffer, and the
+    // debug one is filtered out, so the failed flush still drops it.
+    logger.extend([
+        LogEntry::new(time("10:01:00"), LogLevel::Debug, "in window, filtered"),
+        LogEntry::new(time("09:00:00"), LogLevel::Info, "before"),
+        LogEntry::new(time("10:02:00"), LogLevel::Info, "in window"),
+        LogEntry::new(time("11:00:00"), LogLevel::Info, "after"),
+    ]);
+
+    assert!(logger
+        .flush_between(time("10:00:00"), time("10:30:00"))
+        .is_err());
+    let left: Vec<&str> = logger.iter().map(|entry| entry.message()).collect();
+    assert_eq!(left, ["before", "in window", "after"]);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.log");
+    logger.set_file_path(path.clone());
+    logger.flush().unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "[09:00] INFO: before\n[10:02] INFO: in window\n[11:00] INFO: after\n"
+    );
+}
+
+#[test]
+fn clone_config_copies_settings_but_not_entries() {
+    use simple_logger::{LogFormat, LogLevel};
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut original = Logger::builder()
+        .file_path(dir.path().join("original.log"))
+        .level(LogLevel::Warn)
+        .timestamp_format(String::from("ts"))
+        .build()
+        .with_format(LogFormat::Text)
+        .with_dedup(true);
+    original.info("dropped by the level");
+    original.warn("buffered in the original only");
+
+    let mut copy = original.clone_config();
+    assert!(copy.is_empty());
+    assert!(copy.is_flushed());
+    assert!(!copy.enabled(LogLevel::Info));
+    copy.set_file_path(dir.path().join("copy.log"));
+
+    for logger in [&mut original, &mut copy] {
+        logger.error("disk full");
+        logger.error("disk full");
+        logger.flush().unwrap();
+    }
+
+    let original = fs::read_to_string(dir.path().join("original.log")).unwrap();
+    let copy = fs::read_to_string(dir.path().join("copy.log")).unwrap();
+    assert_eq!(original.lines().count(), 2);
+    assert_eq!(copy.lines().count(), 1);
+    assert!(original.ends_with(&copy));
+    assert_eq!(copy, "[ts] ERROR: disk full (repeated 2 times)\n");
+}
+
+#[test]
+fn flush_and_clear_counts_only_written_lines() {
+    use simple_logger::LogLevel;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("errors.log");
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .level(LogLevel::Error)
+        .build();
+    logger.debug("cache miss");
+    logger.info("request handled");
+    logger.error("request failed");
+    logger.warn("slow request");
+    logger.error("database down");
+
+    assert_eq!(logger.flush_and_clear().unwrap(), 2);
+    assert!(logger.is_empty());
+    assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 2);
+    assert_eq!(logger.flush_and_clear().unwrap(), 0);
+}
@@ -0,0 +1,668 @@
+use simple_logger::{LogFormat, LogLevel, Logger, TimePrecision};
+use std::fs;
+
+#[test]
+fn json_format_writes_one_object_per_line() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.json");
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .build()
+        .with_format(LogFormat::Json);
+
+    logger.info("plain message");
+    logger.error("said \"no\"\nthen left");
+    logger.flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let objects: Vec<serde_json::Value> = contents
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert_eq!(objects.len(), 2);
+    assert_eq!(objects[0]["level"], "INFO");
+    assert_eq!(objects[0]["severity"], 6);
+    assert_eq!(objects[0]["message"], "plain message");
+    assert_eq!(objects[1]["level"], "ERROR");
+    assert_eq!(objects[1]["message"], "said \"no\"\nthen left");
+    let timestamp = objects[1]["timestamp"].as_str().unwrap();
+    assert!(chrono::DateTime::parse_from_rfc3339(timestamp).is_ok());
+}
+
+#[test]
+fn text_format_appends_fields_after_message() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fields.log");
+    let mut logger = Logger::builder().file_path(path.clone()).build();
+
+    logger.info_with("request done", &[("status", "200"), ("ms", "12")]);
+    logger.info("no context");
+    logger.flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert!(lines[0].ends_with("INFO: request done status=200 ms=12"));
+    assert!(lines[1].ends_with("INFO: no context"));
+}
+
+#[test]
+fn json_format_nests_fields() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fields.json");
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .build()
+        .with_format(LogFormat::Json);
+
+    logger.warn_with("slow query", &[("table", "users"), ("ms", "950")]);
+    logger.warn("no context");
+    logger.flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let objects: Vec<serde_json::Value> = contents
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(objects[0]["fields"]["table"], "users");
+    assert_eq!(objects[0]["fields"]["ms"], "950");
+    assert!(objects[1].get("fields").is_none());
+}
+
+#[test]
+fn custom_timestamp_format_is_used() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("timestamps.log");
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .build()
+        .with_timestamp_format(String::from("%d/%m/%Y"));
+
+    logger.info("custom");
+    logger.flush().unwrap();
+
+    let expected = format!(
+        "[{}] INFO: custom\n",
+        chrono::Local::now().format("%d/%m/%Y")
+    );
+    assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+}
+
+#[test]
+fn default_timestamp_uses_colons() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("default.log");
+    let mut logger = Logger::builder().file_path(path.clone()).build();
+
+    logger.info("default");
+    logger.flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let timestamp = &contents[1..contents.find(']').unwrap()];
+    assert!(chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S").is_ok());
+}
+
+#[test]
+fn millisecond_precision_distinguishes_rapid_entries() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("millis.log");
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .build()
+        .with_time_precision(TimePrecision::Millis);
+
+    logger.info("first");
+    std::thread::sleep(std::time::Duration::from_millis(2));
+    logger.info("second");
+    logger.flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let fractions: Vec<&str> = contents
+        .lines()
+        .map(|line| {
+            let timestamp = &line[1..line.find(']').unwrap()];
+            let (_, fraction) = timestamp.rsplit_once('.').unwrap();
+            assert_eq!(fraction.len(), 3);
+            fraction
+        })
+        .collect();
+    assert_ne!(fractions[0], fractions[1]);
+}
+
+#[test]
+fn utc_timestamps_end_in_z() {
+    let dir = tempfile::tempdir().unwrap();
+    let utc_path = dir.path().join("utc.log");
+    let mut logger = Logger::builder()
+        .file_path(utc_path.clone())
+        .build()
+        .with_utc(true);
+    logger.info("in utc");
+    logger.flush().unwrap();
+
+    let contents = fs::read_to_string(&utc_path).unwrap();
+    let timestamp = &contents[1..contents.find(']').unwrap()];
+    let parsed = chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%SZ").unwrap();
+    let drift = chrono::Utc::now().naive_utc() - parsed;
+    assert!(drift.num_seconds().abs() < 5);
+
+    let local_path = dir.path().join("local.log");
+    let mut logger = Logger::builder().file_path(local_path.clone()).build();
+    logger.info("in local time");
+    logger.flush().unwrap();
+
+    let contents = fs::read_to_string(&local_path).unwrap();
+    let timestamp = &contents[1..contents.find(']').unwrap()];
+    assert!(!timestamp.ends_with('Z'));
+    assert!(chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S").is_ok());
+}
+
+#[test]
+fn custom_level_labels_replace_defaults() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("labels.log");
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .build()
+        .with_level_labels(|level| match level {
+            LogLevel::Error => "ERR",
+            LogLevel::Warn => "WRN",
+            _ => "INF",
+        });
+
+    logger.error("failed");
+    logger.warn("careful");
+    logger.info("ok");
+    logger.flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert!(lines[0].ends_with("ERR: failed"));
+    assert!(lines[1].ends_with("WRN: careful"));
+    assert!(lines[2].ends_with("INF: ok"));
+}
+
+#[test]
+fn custom_formatter_renders_each_line() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("formatter.log");
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .build()
+        .with_formatter(|entry| format!("{}|{}", entry.message(), entry.level()));
+
+    logger.info("started");
+    logger.error("crashed");
+    logger.flush().unwrap();
+
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "started|INFO\ncrashed|ERROR\n"
+    );
+}
+
+#[test]
+fn ndjson_header_written_only_when_file_is_created() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ndjson");
+
+    for run in ["first run", "second run"] {
+        let mut logger = Logger::builder()
+            .file_path(path.clone())
+            .build()
+            .with_format(LogFormat::Ndjson)
+            .with_header(true);
+        logger.info(run);
+        logger.flush().unwrap();
+    }
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let objects: Vec<serde_json::Value> = contents
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert_eq!(objects.len(), 3);
+    assert_eq!(objects[0]["pid"], std::process::id());
+    assert!(objects[0]["app"].is_string());
+    assert!(objects[0]["started"].is_string());
+    assert_eq!(objects[1]["message"], "first run");
+    assert_eq!(objects[2]["message"], "second run");
+}
+
+#[test]
+fn dedup_collapses_consecutive_duplicates() {
+    let mut logger = Logger::builder()
+        .build()
+        .with_timestamp_format(String::from("ts"))
+        .with_dedup(true);
+    for _ in 0..3 {
+        logger.warn("retrying");
+    }
+    logger.error("retrying");
+    logger.info("connected");
+    logger.warn("retrying");
+    logger.warn("retrying");
+
+    let mut buffer = Vec::new();
+    logger.flush_to_writer(&mut buffer).unwrap();
+
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "[ts] WARN: retrying (repeated 3 times)\n\
+         [ts] ERROR: retrying\n\
+         [ts] INFO: connected\n\
+         [ts] WARN: retrying (repeated 2 times)\n"
+    );
+}
+
+#[test]
+fn redactor_masks_secrets_before_they_reach_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("redacted.log");
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .build()
+        .with_redactor(simple_logger::redact_patterns(&["sk-live-1234"]));
+
+    logger.info("calling API with key sk-live-1234");
+    logger.flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    assert!(!contents.contains("sk-live-1234"));
+    assert!(contents.contains("calling API with key ***"));
+}
+
+#[test]
+fn redactor_runs_before_long_messages_are_cut() {
+    let mut logger = Logger::builder()
+        .build()
+        .with_max_message_len(20)
+        .with_redactor(simple_logger::redact_patterns(&["sk-live-123456"]));
+
+    logger.info("using key sk-live-123456 for the request");
+
+    let message = logger.iter().next().unwrap().message();
+    assert!(!message.contains("sk-live"), "{message}");
+    assert_eq!(message, "using key *** for th…[truncated]");
+    logger.clear();
+}
+
+#[test]
+fn line_ending_sets_the_exact_terminator() {
+    use simple_logger::LineEnding;
+
+    let native: &[u8] = if cfg!(windows) { b"\r\n" } else { b"\n" };
+    let cases: [(LineEnding, &[u8]); 3] = [
+        (LineEnding::Lf, b"\n"),
+        (LineEnding::CrLf, b"\r\n"),
+        (LineEnding::Native, native),
+    ];
+
+    for (line_ending, terminator) in cases {
+        let mut logger = Logger::builder()
+            .build()
+            .with_timestamp_format(String::from("ts"))
+            .with_line_ending(line_ending);
+        logger.info("one");
+        logger.info("two");
+
+        let mut buffer = Vec::new();
+        logger.flush_to_writer(&mut buffer).unwrap();
+
+        let expected = [
+            b"[ts] INFO: one".as_slice(),
+            terminator,
+            b"[ts] INFO: two",
+            terminator,
+        ]
+        .concat();
+        assert_eq!(buffer, expected, "{line_ending:?}");
+    }
+}
+
+#[test]
+fn logfmt_quotes_values_only_when_needed() {
+    let mut logger = Logger::builder().build().with_format(LogFormat::Logfmt);
+    logger.info("started");
+    logger.warn_with(
+        "disk \"sda\" almost full",
+        &[("free", "2%"), ("mount", "/var lib")],
+    );
+
+    let mut buffer = Vec::new();
+    logger.flush_to_writer(&mut buffer).unwrap();
+    let output = String::from_utf8(buffer).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+
+    let (ts, rest) = lines[0].split_once(' ').unwrap();
+    assert!(chrono::DateTime::parse_from_rfc3339(ts.strip_prefix("ts=").unwrap()).is_ok());
+    assert_eq!(rest, "level=info severity=6 msg=started");
+    assert!(lines[1].ends_with(
+        r#" level=warn severity=4 msg="disk \"sda\" almost full" free=2% mount="/var lib""#
+    ));
+}
+
+#[test]
+fn origin_adds_hostname_and_pid() {
+    let mut logger = Logger::builder()
+        .build()
+        .with_timestamp_format(String::from("ts"))
+        .with_origin(true);
+    logger.info("hello");
+    let mut buffer = Vec::new();
+    logger.flush_to_writer(&mut buffer).unwrap();
+    let line = String::from_utf8(buffer).unwrap();
+    assert!(line.ends_with(&format!(":{}] INFO: hello\n", std::process::id())));
+
+    let mut logger = Logger::builder()
+        .build()
+        .with_format(LogFormat::Json)
+        .with_origin(true);
+    logger.info("hello");
+    let mut buffer = Vec::new();
+    logger.flush_to_writer(&mut buffer).unwrap();
+    let object: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+    assert_eq!(object["pid"], std::process::id());
+    assert!(object["hostname"].is_string());
+}
+
+#[test]
+fn frozen_clock_gives_exact_timestamps() {
+    use chrono::{DateTime, FixedOffset};
+    use simple_logger::Clock;
+
+    struct Frozen;
+
+    impl Clock for Frozen {
+        fn now(&self) -> DateTime<FixedOffset> {
+            DateTime::parse_from_rfc3339("2024-06-01T12:30:45+02:00").unwrap()
+        }
+    }
+
+    let mut logger = Logger::builder().build().with_clock(Box::new(Frozen));
+    logger.info("local");
+    let mut buffer = Vec::new();
+    logger.flush_to_writer(&mut buffer).unwrap();
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "[2024-06-01 12:30:45] INFO: local\n"
+    );
+
+    let mut logger = Logger::builder()
+        .build()
+        .with_clock(Box::new(Frozen))
+        .with_utc(true);
+    logger.info("utc");
+    let mut buffer = Vec::new();
+    logger.flush_to_writer(&mut buffer).unwrap();
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "[2024-06-01 10:30:45Z] INFO: utc\n"
+    );
+}
+
+#[test]
+fn csv_records_round_trip_through_a_csv_reader() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.csv");
+    let messages = [
+        "started",
+        "disk \"sda\", almost full",
+        "stack trace:\nframe 1\r\nframe 2",
+    ];
+
+    for message in messages {
+        let mut logger = Logger::builder()
+            .file_path(path.clone())
+            .build()
+            .with_format(LogFormat::Csv)
+            .with_timestamp_format(String::from("ts"));
+        logger.warn(message);
+        logger.flush().unwrap();
+    }
+
+    let mut reader = csv::Reader::from_path(&path).unwrap();
+    assert_eq!(
+        reader.headers().unwrap(),
+        vec!["timestamp", "level", "message"]
+    );
+    let records: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+    assert_eq!(records.len(), messages.len());
+    for (record, message) in records.iter().zip(messages) {
+        assert_eq!(record, vec!["ts", "WARN", message]);
+    }
+}
+
+#[test]
+fn bom_starts_new_files_only() {
+    let dir = tempfile::tempdir().unwrap();
+    let log_to = |name: &str, bom: bool| {
+        let path = dir.path().join(name);
+        for message in ["first run", "second run"] {
+            let mut logger = Logger::builder()
+                .file_path(path.clone())
+                .build()
+                .with_bom(bom);
+            logger.info(message);
+            logger.flush().unwrap();
+        }
+        fs::read(&path).unwrap()
+    };
+
+    let with_bom = log_to("bom.log", true);
+    assert_eq!(with_bom[..3], [0xEF, 0xBB, 0xBF]);
+    assert!(with_bom[3..].starts_with(b"["));
+    let bytes = with_bom.windows(3).filter(|w| *w == [0xEF, 0xBB, 0xBF]);
+    assert_eq!(bytes.count(), 1);
+
+    let without_bom = log_to("plain.log", false);
+    assert!(without_bom.starts_with(b"["));
+}
+
+#[test]
+fn session_header_only_starts_new_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.log");
+    for message in ["first run", "second run"] {
+        let mut logger = Logger::builder()
+            .file_path(path.clone())
+            .build()
+            .with_session_header(true);
+        logger.info(message);
+        logger.flush().unwrap();
+    }
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("# session started "));
+    assert!(lines[0].contains(&format!(" pid={} ", std::process::id())));
+    assert!(lines[0].contains(&format!(" os={} ", std::env::consts::OS)));
+    assert!(lines[0].ends_with(&format!(" version={}", env!("CARGO_PKG_VERSION"))));
+    assert!(lines[1].ends_with("INFO: first run"));
+    assert!(lines[2].ends_with("INFO: second run"));
+}
+
+#[test]
+fn backtraces_follow_only_the_entries_that_captured_one() {
+    let mut logger = Logger::builder()
+        .build()
+        .with_timestamp_format(String::from("ts"));
+    logger.error("plain failure");
+    logger.error_with_backtrace("traced failure");
+    logger.info("carrying on");
+    assert!(logger.iter().next().unwrap().backtrace().is_none());
+    assert!(logger.iter().nth(1).unwrap().backtrace().is_some());
+
+    let mut buffer = Vec::new();
+    logger.flush_to_writer(&mut buffer).unwrap();
+    let output = String::from_utf8(buffer).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+
+    assert_eq!(lines[0], "[ts] ERROR: plain failure");
+    assert_eq!(lines[1], "[ts] ERROR: traced failure");
+    let frames = &lines[2..lines.len() - 1];
+    assert!(!frames.is_empty());
+    assert!(frames.iter().all(|frame| frame.starts_with("    ")));
+    assert!(frames
+        .iter()
+        .any(|frame| frame.contains("error_with_backtrace")));
+    assert_eq!(lines[lines.len() - 1], "[ts] INFO: carrying on");
+}
+
+#[test]
+fn epoch_timestamps_are_numbers_since_the_epoch() {
+    use chrono::DateTime;
+    use simple_logger::{LogEntry, TimeUnit, TimestampMode};
+
+    let timestamp = DateTime::parse_from_rfc3339("2024-06-01T12:30:45.123456+02:00").unwrap();
+    let render = |format: LogFormat, unit: TimeUnit| {
+        let mut logger = Logger::builder()
+            .build()
+            .with_format(format)
+            .with_timestamp_mode(TimestampMode::Epoch(unit));
+        logger.extend([LogEntry::new(timestamp, LogLevel::Info, "tick")]);
+        let mut buffer = Vec::new();
+        logger.flush_to_writer(&mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    };
+
+    assert_eq!(
+        render(LogFormat::Text, TimeUnit::Millis),
+        format!("[{}] INFO: tick\n", timestamp.timestamp_millis())
+    );
+    assert_eq!(
+        render(LogFormat::Logfmt, TimeUnit::Seconds),
+        format!(
+            "ts={} level=info severity=6 msg=tick\n",
+            timestamp.timestamp()
+        )
+    );
+    let json: serde_json::Value =
+        serde_json::from_str(&render(LogFormat::Json, TimeUnit::Micros)).unwrap();
+    assert_eq!(
+        json["timestamp"].as_i64(),
+        Some(timestamp.timestamp_micros())
+    );
+}
+
+#[test]
+fn relative_timestamps_count_from_logger_creation() {
+    use simple_logger::TimestampMode;
+    use std::thread;
+    use std::time::Duration;
+
+    let mut logger = Logger::builder()
+        .build()
+        .with_timestamp_mode(TimestampMode::Relative);
+    logger.info("first");
+    thread::sleep(Duration::from_millis(20));
+    logger.info("second");
+    let mut buffer = Vec::new();
+    logger.flush_to_writer(&mut buffer).unwrap();
+
+    let offsets: Vec<u64> = String::from_utf8(buffer)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let offset = line.strip_prefix("[+").unwrap().split_once("s]").unwrap().0;
+            offset.replace('.', "").parse().unwrap()
+        })
+        .collect();
+    assert_eq!(offsets.len(), 2);
+    assert!(offsets[1] >= offsets[0] + 20, "{offsets:?}");
+}
+
+#[test]
+fn relative_timestamps_follow_the_clock() {
+    use chrono::{DateTime, FixedOffset};
+    use simple_logger::{Clock, TimestampMode};
+    use std::sync::{Arc, Mutex};
+
+    struct Manual(Arc<Mutex<DateTime<FixedOffset>>>);
+
+    impl Clock for Manual {
+        fn now(&self) -> DateTime<FixedOffset> {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    let time = Arc::new(Mutex::new(
+        DateTime::parse_from_rfc3339("2001-01-01T00:00:00+00:00").unwrap(),
+    ));
+    let mut logger = Logger::builder()
+        .build()
+        .with_clock(Box::new(Manual(Arc::clone(&time))))
+        .with_timestamp_mode(TimestampMode::Relative);
+    *time.lock().unwrap() += chrono::Duration::milliseconds(1500);
+    logger.info("later");
+    let mut buffer = Vec::new();
+    logger.flush_to_writer(&mut buffer).unwrap();
+
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "[+1.500s] INFO: later\n"
+    );
+}
+
+#[test]
+fn json_array_writes_each_flush_as_one_array() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.json");
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .build()
+        .with_format(LogFormat::JsonArray);
+
+    logger.info("started");
+    logger.warn("slow, \"very\" slow");
+    logger.error("failed");
+    logger.flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let array: serde_json::Value = serde_json::from_str(&contents).unwrap();
+    let entries = array.as_array().unwrap();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[1]["message"], "slow, \"very\" slow");
+    assert_eq!(entries[2]["level"], "ERROR");
+
+    logger.info("stopped");
+    logger.flush().unwrap();
+    let contents = fs::read_to_string(&path).unwrap();
+    let arrays: Vec<serde_json::Value> = contents
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(arrays.len(), 2);
+    assert_eq!(arrays[1].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn severity_map_numbers_levels_in_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.json");
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .build()
+        .with_format(LogFormat::Json)
+        .with_severity_map(|level| match level {
+            LogLevel::Error => 50,
+            LogLevel::Warn => 40,
+            _ => 30,
+        });
+
+    logger.error("failed");
+    logger.warn("slow");
+    logger.info("started");
+    logger.flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let severities: Vec<u64> = contents
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .map(|object| object["severity"].as_u64().unwrap())
+        .collect();
+    assert_eq!(severities, [50, 40, 30]);
+    assert_eq!(LogLevel::Error.syslog_severity(), 3);
+}
@@ -0,0 +1,28 @@
+use simple_logger::Logger;
+use std::fs;
+
+fn handle_request() {
+    Logger::global().lock().info("request handled");
+}
+
+fn shut_down() {
+    Logger::global().lock().warn("shutting down");
+}
+
+#[test]
+fn global_logger_is_shared_across_functions() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("global.log");
+    Logger::init_global(Logger::builder().file_path(path.clone()).build()).unwrap();
+
+    handle_request();
+    shut_down();
+    Logger::global().flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    assert!(contents.contains("INFO: request handled"));
+    assert!(contents.contains("WARN: shutting down"));
+
+    let second = Logger::init_global(Logger::builder().build());
+    assert!(second.is_err());
+}
@@ -0,0 +1,74 @@
+use simple_logger::LogLevel;
+
+#[test]
+fn parses_levels_case_insensitively() {
+    assert_eq!("trace".parse(), Ok(LogLevel::Trace));
+    assert_eq!("Debug".parse(), Ok(LogLevel::Debug));
+    assert_eq!("INFO".parse(), Ok(LogLevel::Info));
+    assert_eq!("wArN".parse(), Ok(LogLevel::Warn));
+    assert_eq!("Error".parse(), Ok(LogLevel::Error));
+    assert_eq!("DEFAULT".parse(), Ok(LogLevel::Default));
+}
+
+#[test]
+fn unknown_level_is_a_descriptive_error() {
+    let err = "verbose".parse::<LogLevel>().unwrap_err();
+    assert!(err.to_string().contains("`verbose`"));
+    assert!("".parse::<LogLevel>().is_err());
+}
+
+#[test]
+fn displays_uppercase_labels() {
+    assert_eq!(LogLevel::Default.to_string(), "DEFAULT");
+    assert_eq!(LogLevel::Trace.to_string(), "TRACE");
+    assert_eq!(LogLevel::Debug.to_string(), "DEBUG");
+    assert_eq!(LogLevel::Info.to_string(), "INFO");
+    assert_eq!(LogLevel::Warn.to_string(), "WARN");
+    assert_eq!(LogLevel::Error.to_string(), "ERROR");
+}
+
+#[test]
+fn log_accepts_every_level_at_runtime() {
+    let levels = [
+        LogLevel::Trace,
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ];
+    let mut logger = simple_logger::Logger::builder().build();
+    for level in levels {
+        logger.log(level, format!("at {level}"));
+    }
+
+    let logged: Vec<(LogLevel, &str)> = logger
+        .iter()
+        .map(|entry| (entry.level(), entry.message()))
+        .collect();
+    assert_eq!(
+        logged,
+        [
+            (LogLevel::Trace, "at TRACE"),
+            (LogLevel::Debug, "at DEBUG"),
+            (LogLevel::Info, "at INFO"),
+            (LogLevel::Warn, "at WARN"),
+            (LogLevel::Error, "at ERROR"),
+        ]
+    );
+    logger.clear();
+}
+
+#[test]
+fn set_level_filters_without_the_environment() {
+    let mut logger = simple_logger::Logger::new(std::env::temp_dir().join("unused.log"));
+    logger.set_level(LogLevel::Error);
+    assert!(!logger.enabled(LogLevel::Warn));
+
+    logger.warn("disk almost full");
+    logger.error("disk full");
+    let mut buffer = Vec::new();
+    logger.flush_to_writer(&mut buffer).unwrap();
+    let output = String::from_utf8(buffer).unwrap();
+    assert_eq!(output.lines().count(), 1);
+    assert!(output.ends_with("ERROR: disk full\n"));
+}
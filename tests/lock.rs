@@ -0,0 +1,64 @@
+#![cfg(unix)]
+
+use simple_logger::Logger;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+const PATH_ENV: &str = "SIMPLE_LOGGER_LOCK_TEST_PATH";
+const WRITER_ENV: &str = "SIMPLE_LOGGER_LOCK_TEST_WRITER";
+const BATCHES: usize = 50;
+const BATCH_SIZE: usize = 100;
+
+/// Runs as one of the writer processes spawned by the test below, flushing
+/// batches big enough to take several writes each.
+fn write_batches(writer: &str, path: PathBuf) {
+    let mut logger = Logger::builder().file_path(path).build();
+    for batch in 0..BATCHES {
+        for index in 0..BATCH_SIZE {
+            logger.info(format!("{writer} {batch} {index} {}", writer.repeat(200)));
+        }
+        logger.flush().unwrap();
+    }
+}
+
+#[test]
+fn concurrent_processes_write_whole_batches() {
+    if let Some(path) = env::var_os(PATH_ENV) {
+        write_batches(&env::var(WRITER_ENV).unwrap(), path.into());
+        return;
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("shared.log");
+    let writers: Vec<_> = ["a", "b"]
+        .into_iter()
+        .map(|writer| {
+            Command::new(env::current_exe().unwrap())
+                .args(["--exact", "concurrent_processes_write_whole_batches"])
+                .env(PATH_ENV, &path)
+                .env(WRITER_ENV, writer)
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+    for mut writer in writers {
+        assert!(writer.wait().unwrap().success());
+    }
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents
+        .lines()
+        .map(|line| line.split_once("INFO: ").unwrap().1)
+        .collect();
+    assert_eq!(lines.len(), 2 * BATCHES * BATCH_SIZE);
+    for batch in lines.chunks(BATCH_SIZE) {
+        let (writer, rest) = batch[0].split_once(' ').unwrap();
+        let number = rest.split(' ').next().unwrap();
+        for (index, line) in batch.iter().enumerate() {
+            let expected = format!("{writer} {number} {index} {}", writer.repeat(200));
+            assert_eq!(*line, expected);
+        }
+    }
+}
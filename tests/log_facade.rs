@@ -0,0 +1,26 @@
+#![cfg(feature = "log")]
+
+use simple_logger::{LogLevel, Logger};
+use std::fs;
+
+#[test]
+fn log_macros_reach_the_logger_after_init() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("facade.log");
+    Logger::builder()
+        .file_path(path.clone())
+        .level(LogLevel::Info)
+        .build()
+        .init()
+        .unwrap();
+
+    log::info!("listening on port {}", 8080);
+    log::warn!("cache cold");
+    log::debug!("filtered out");
+    log::logger().flush();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    assert!(contents.contains("INFO: listening on port 8080"));
+    assert!(contents.contains("WARN: cache cold"));
+    assert!(!contents.contains("filtered out"));
+}
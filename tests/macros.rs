@@ -0,0 +1,20 @@
+use simple_logger::{info, Logger};
+use std::fs;
+
+#[test]
+fn macros_record_the_call_site() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("macros.log");
+    let mut logger = Logger::builder().file_path(path.clone()).build();
+
+    let id = 7;
+    info!(logger, "user {} logged in", id);
+    let line = line!() - 1;
+    logger.info("no location");
+    logger.flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert!(lines[0].ends_with(&format!("INFO: user 7 logged in (tests/macros.rs:{line})")));
+    assert!(lines[1].ends_with("INFO: no location"));
+}
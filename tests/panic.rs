@@ -0,0 +1,23 @@
+use simple_logger::{Logger, SharedLogger};
+use std::fs;
+use std::panic;
+use std::sync::Arc;
+
+#[test]
+fn panic_hook_logs_and_flushes_the_panic() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("panics.log");
+    let logger = Arc::new(SharedLogger::new(
+        Logger::builder().file_path(path.clone()).build(),
+    ));
+    Logger::install_panic_hook(Arc::clone(&logger));
+
+    let result = panic::catch_unwind(|| panic!("worker exploded"));
+    assert!(result.is_err());
+
+    let contents = fs::read_to_string(&path).unwrap();
+    assert!(contents.contains("ERROR: thread '"));
+    assert!(contents.contains("panicked at tests/panic.rs:"));
+    assert!(contents.contains("worker exploded"));
+    assert!(logger.lock().is_empty());
+}
@@ -0,0 +1,50 @@
+#[cfg(windows)]
+#[test]
+fn windows_default_path_uses_program_data() {
+    let path = simple_logger::default_log_path();
+    let path = path.to_string_lossy();
+    assert!(path.contains("ProgramData"));
+    assert!(!path.contains("PorgramData"));
+}
+
+#[test]
+fn default_path_is_a_log_file() {
+    let path = simple_logger::default_log_path();
+    assert_eq!(path.extension().unwrap(), "log");
+    assert!(!path.is_dir());
+
+    // Without fallbacks, so a failure to write the default location shows.
+    let mut logger = simple_logger::Logger::new(path.clone()).with_fallback_paths([]);
+    logger.info("written to the default location");
+    match logger.flush() {
+        Ok(()) => {}
+        // Non-root runs usually can't write to the system log directory.
+        Err(simple_logger::LoggerError::Io(err))
+            if err.kind() == std::io::ErrorKind::PermissionDenied =>
+        {
+            return
+        }
+        Err(err) => panic!("flush failed: {err}"),
+    }
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(contents.contains("written to the default location"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn directory_file_path_gets_a_default_file_name() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut logger = simple_logger::Logger::builder()
+        .file_path(dir.path().to_path_buf())
+        .build();
+
+    let path = logger.file_path();
+    assert_eq!(path.parent().unwrap(), dir.path());
+    assert_eq!(path.extension().unwrap(), "log");
+
+    logger.info("written inside the directory");
+    logger.flush().unwrap();
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(contents.contains("written inside the directory"));
+}
@@ -0,0 +1,83 @@
+use chrono::{Local, TimeZone};
+use simple_logger::{LogEntry, LogLevel, Logger, Rotation};
+use std::fs;
+
+#[test]
+fn size_rotation_shifts_backups() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.log");
+    // Every line is ~36 bytes, so each file holds a single entry.
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .max_file_size(50)
+        .max_backups(2)
+        .build();
+
+    logger.info("entry 1");
+    logger.info("entry 2");
+    logger.flush().unwrap();
+    logger.info("entry 3");
+    logger.flush().unwrap();
+
+    let read = |name: &str| fs::read_to_string(dir.path().join(name)).unwrap();
+    assert!(read("app.log").ends_with("entry 3\n"));
+    assert!(read("app.log.1").ends_with("entry 2\n"));
+    assert!(read("app.log.2").ends_with("entry 1\n"));
+
+    logger.info("entry 4");
+    logger.flush().unwrap();
+    assert!(read("app.log").ends_with("entry 4\n"));
+    assert!(read("app.log.2").ends_with("entry 2\n"));
+    assert!(!dir.path().join("app.log.3").exists());
+}
+
+#[test]
+fn daily_rotation_routes_entries_by_their_own_date() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.log");
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .rotation(Rotation::Daily)
+        .build();
+
+    let before_midnight = Local.with_ymd_and_hms(2024, 5, 31, 23, 59, 59).unwrap();
+    let after_midnight = Local.with_ymd_and_hms(2024, 6, 1, 0, 0, 1).unwrap();
+    logger.push(LogEntry::new(
+        before_midnight,
+        LogLevel::Info,
+        "last of may",
+    ));
+    logger.push(LogEntry::new(
+        after_midnight,
+        LogLevel::Info,
+        "first of june",
+    ));
+    logger.flush().unwrap();
+
+    let may = fs::read_to_string(dir.path().join("app-2024-05-31.log")).unwrap();
+    let june = fs::read_to_string(dir.path().join("app-2024-06-01.log")).unwrap();
+    assert!(may.contains("last of may") && !may.contains("first of june"));
+    assert!(june.contains("first of june") && !june.contains("last of may"));
+    assert!(!path.exists());
+}
+
+#[test]
+fn reopen_creates_a_new_file_after_external_rename() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.log");
+    let moved = dir.path().join("app.log.old");
+    let mut logger = Logger::builder().file_path(path.clone()).build();
+
+    logger.info("before rotation");
+    logger.flush().unwrap();
+    fs::rename(&path, &moved).unwrap();
+
+    logger.reopen().unwrap();
+    logger.info("after rotation");
+    logger.flush().unwrap();
+
+    let old = fs::read_to_string(&moved).unwrap();
+    let new = fs::read_to_string(&path).unwrap();
+    assert!(old.contains("before rotation") && !old.contains("after rotation"));
+    assert!(new.contains("after rotation") && !new.contains("before rotation"));
+}
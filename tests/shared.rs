@@ -0,0 +1,132 @@
+// The `&format!(..)` call sites predate owned messages, and check they still compile.
+#![allow(clippy::needless_borrows_for_generic_args)]
+
+use simple_logger::{Logger, SharedLogger};
+use std::fs;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn entries_from_all_threads_are_captured() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("threads.log");
+    let logger = Arc::new(SharedLogger::new(
+        Logger::builder().file_path(path.clone()).build(),
+    ));
+
+    let handles: Vec<_> = (0..8)
+        .map(|worker| {
+            let logger = Arc::clone(&logger);
+            thread::spawn(move || {
+                for i in 0..250 {
+                    logger.info(&format!("worker {worker} message {i}"));
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    logger.flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    assert_eq!(contents.lines().count(), 8 * 250);
+}
+
+#[test]
+fn thread_info_names_the_logging_thread() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("thread-names.log");
+    let logger = Arc::new(SharedLogger::new(
+        Logger::builder()
+            .file_path(path.clone())
+            .build()
+            .with_thread_info(true),
+    ));
+
+    let handles: Vec<_> = (0..3)
+        .map(|worker| {
+            let logger = Arc::clone(&logger);
+            thread::Builder::new()
+                .name(format!("worker-{worker}"))
+                .spawn(move || logger.info("working"))
+                .unwrap()
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    logger.flush().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    for worker in 0..3 {
+        assert!(contents.contains(&format!("] [worker-{worker}] INFO: working")));
+    }
+}
+
+#[test]
+fn async_logger_writes_everything_before_shutdown_returns() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("async.log");
+    let logger = simple_logger::AsyncLogger::new(Logger::builder().file_path(path.clone()).build());
+
+    for i in 0..1000 {
+        logger.info(&format!("message {i}"));
+    }
+    logger.error("last message");
+    logger.shutdown().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 1001);
+    assert!(lines[0].ends_with("message 0"));
+    assert!(lines[1000].ends_with("ERROR: last message"));
+}
+
+#[test]
+fn full_queue_drops_newest_entries_and_counts_them() {
+    use simple_logger::{AsyncLogger, LogEntry, Overflow, Sink};
+    use std::sync::mpsc::{self, Receiver, Sender};
+
+    // Holds the worker on the first entry until the test lets it go.
+    struct Gate {
+        blocked: Sender<()>,
+        release: Receiver<()>,
+    }
+
+    impl Sink for Gate {
+        fn write_entry(&mut self, entry: &LogEntry) -> std::io::Result<()> {
+            if entry.message() == "entry 0" {
+                self.blocked.send(()).unwrap();
+                self.release.recv().unwrap();
+            }
+            Ok(())
+        }
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bounded.log");
+    let (blocked_tx, blocked) = mpsc::channel();
+    let (release, release_rx) = mpsc::channel();
+    let mut inner = Logger::builder().file_path(path.clone()).build();
+    inner.add_sink(Gate {
+        blocked: blocked_tx,
+        release: release_rx,
+    });
+    let logger = AsyncLogger::with_capacity(inner, 1).with_overflow(Overflow::DropNewest);
+
+    logger.info("entry 0");
+    blocked.recv().unwrap();
+    for i in 1..10 {
+        logger.info(&format!("entry {i}"));
+    }
+    release.send(()).unwrap();
+    logger.shutdown().unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].ends_with("entry 0"));
+    assert!(lines[1].ends_with("entry 1"));
+    assert!(lines[2].ends_with("WARN: dropped 8 entries while the queue was full"));
+}
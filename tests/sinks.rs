@@ -0,0 +1,215 @@
+use simple_logger::{LogEntry, Logger, Sink};
+use std::fs;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+impl Sink for Recorder {
+    fn write_entry(&mut self, entry: &LogEntry) -> io::Result<()> {
+        self.0.lock().unwrap().push(format!("{:?}", entry));
+        Ok(())
+    }
+}
+
+#[test]
+fn entries_fan_out_to_every_sink_and_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fanout.log");
+    let first = Recorder::default();
+    let second = Recorder::default();
+    let mut logger = Logger::builder().file_path(path.clone()).build();
+    logger.add_sink(first.clone());
+    logger.add_sink(second.clone());
+
+    logger.info("one");
+    logger.error("two");
+    assert_eq!(first.0.lock().unwrap().len(), 2);
+    assert_eq!(second.0.lock().unwrap().len(), 2);
+    assert!(first.0.lock().unwrap()[1].contains("two"));
+
+    logger.flush().unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 2);
+}
+
+#[test]
+fn sinks_only_get_redacted_messages() {
+    let recorder = Recorder::default();
+    let mut logger = Logger::builder()
+        .build()
+        .with_redactor(simple_logger::redact_patterns(&["hunter2"]));
+    logger.add_sink(recorder.clone());
+
+    logger.info("password=hunter2");
+    let recorded = recorder.0.lock().unwrap();
+    assert!(recorded[0].contains("password=***"));
+    assert!(!recorded[0].contains("hunter2"));
+    drop(recorded);
+    logger.clear();
+}
+
+#[test]
+fn tcp_sink_sends_newline_delimited_entries() {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let collector = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        BufReader::new(stream)
+            .lines()
+            .take(2)
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+    });
+
+    let mut logger = Logger::builder().build();
+    logger.add_tcp_sink(addr);
+    logger.info("shipped");
+    logger.error("also shipped");
+    assert!(logger.take_last_error().is_none());
+
+    let lines = collector.join().unwrap();
+    assert!(lines[0].ends_with("INFO: shipped"));
+    assert!(lines[1].ends_with("ERROR: also shipped"));
+    logger.clear();
+}
+
+#[test]
+fn tcp_sink_falls_back_to_a_file_while_disconnected() {
+    let dir = tempfile::tempdir().unwrap();
+    let fallback = dir.path().join("fallback.log");
+    // Nothing listens on a port once its listener is dropped.
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let mut logger = Logger::builder().build();
+    logger.add_sink(
+        simple_logger::TcpSink::new(addr)
+            .with_fallback(&fallback)
+            .unwrap(),
+    );
+    logger.warn("collector down");
+    assert!(logger.take_last_error().is_none());
+
+    let contents = fs::read_to_string(&fallback).unwrap();
+    assert!(contents.ends_with("WARN: collector down\n"));
+    logger.clear();
+}
+
+#[test]
+fn tcp_sink_waits_before_reconnecting() {
+    let dir = tempfile::tempdir().unwrap();
+    let fallback = dir.path().join("fallback.log");
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let mut logger = Logger::builder().build();
+    logger.add_sink(
+        simple_logger::TcpSink::new(addr)
+            .with_fallback(&fallback)
+            .unwrap(),
+    );
+    logger.warn("collector down");
+
+    // The collector is back, but the sink doesn't retry straight away.
+    let listener = std::net::TcpListener::bind(addr).unwrap();
+    listener.set_nonblocking(true).unwrap();
+    logger.warn("still buffering");
+    assert!(listener.accept().is_err());
+
+    let contents = fs::read_to_string(&fallback).unwrap();
+    assert_eq!(contents.lines().count(), 2);
+    logger.clear();
+}
+
+#[test]
+fn sinks_only_get_entries_at_or_above_their_level() {
+    use simple_logger::{ConsoleSink, FileSink, LogLevel};
+    use std::io::Write;
+
+    #[derive(Clone, Default)]
+    struct Screen(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Screen {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let sink_path = dir.path().join("debug.log");
+    let screen = Screen::default();
+    let mut logger = Logger::builder().build();
+    logger.add_sink_with_level(LogLevel::Info, ConsoleSink::writer(screen.clone()));
+    logger.add_sink_with_level(LogLevel::Debug, FileSink::new(&sink_path).unwrap());
+
+    logger.trace("too verbose for both");
+    logger.debug("cache miss");
+    logger.info("request handled");
+    logger.flush_to_writer(&mut io::sink()).unwrap();
+
+    let screen = String::from_utf8(screen.0.lock().unwrap().clone()).unwrap();
+    assert_eq!(screen.lines().count(), 1);
+    assert!(screen.contains("INFO: request handled"));
+    let file = fs::read_to_string(&sink_path).unwrap();
+    let lines: Vec<&str> = file.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("DEBUG: cache miss"));
+    assert!(lines[1].ends_with("INFO: request handled"));
+}
+
+#[test]
+fn sink_levels_ignore_the_file_level() {
+    use simple_logger::{debug, LogLevel};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.log");
+    let verbose = Recorder::default();
+    let follows = Recorder::default();
+    let mut logger = Logger::builder()
+        .file_path(path.clone())
+        .level(LogLevel::Info)
+        .build();
+    logger.add_sink_with_level(LogLevel::Debug, verbose.clone());
+    logger.add_sink(follows.clone());
+
+    logger.debug("cache miss");
+    debug!(logger, "cache miss {}", 2);
+    logger.info("request handled");
+    logger.flush().unwrap();
+
+    assert_eq!(verbose.0.lock().unwrap().len(), 3);
+    assert_eq!(follows.0.lock().unwrap().len(), 1);
+    let contents = fs::read_to_string(&path).unwrap();
+    assert_eq!(contents.lines().count(), 1);
+    assert!(contents.contains("INFO: request handled"));
+}
+
+#[test]
+fn disabled_logger_never_creates_a_file() {
+    use simple_logger::LogLevel;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("disabled.log");
+    let mut logger = Logger::disabled();
+    logger.set_file_path(path.clone());
+
+    logger.error("dropped");
+    logger.info("dropped too");
+    assert!(logger.is_empty());
+    assert!(!logger.enabled(LogLevel::Error));
+    logger.flush().unwrap();
+    logger.finalize().unwrap();
+    assert!(!path.exists());
+}
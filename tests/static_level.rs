@@ -0,0 +1,26 @@
+//! Run with `cargo test --features max_level_info --test static_level`.
+#![cfg(feature = "max_level_info")]
+
+use simple_logger::{debug, info, LogLevel, Logger, STATIC_MAX_LEVEL};
+
+#[test]
+fn calls_below_the_static_max_level_are_compiled_out() {
+    assert_eq!(STATIC_MAX_LEVEL, LogLevel::Info);
+
+    let mut logger = Logger::builder().build();
+    let mut formatted = false;
+    debug!(logger, "{}", {
+        formatted = true;
+        "expensive"
+    });
+    logger.debug("dropped");
+    logger.log(LogLevel::Trace, "dropped");
+    info!(logger, "kept");
+    logger.warn("kept too");
+
+    assert!(!formatted);
+    assert!(!logger.enabled(LogLevel::Debug));
+    let messages: Vec<&str> = logger.iter().map(|entry| entry.message()).collect();
+    assert_eq!(messages, ["kept", "kept too"]);
+    logger.clear();
+}
@@ -0,0 +1,37 @@
+#![cfg(all(unix, feature = "syslog"))]
+
+use chrono::{FixedOffset, TimeZone};
+use simple_logger::{LogEntry, LogLevel, Logger, SyslogSink};
+use std::os::unix::net::UnixDatagram;
+
+#[test]
+fn entries_are_framed_as_rfc_3164() {
+    let dir = tempfile::tempdir().unwrap();
+    let socket_path = dir.path().join("log.sock");
+    let daemon = UnixDatagram::bind(&socket_path).unwrap();
+
+    let mut logger = Logger::builder().build();
+    logger.add_sink(SyslogSink::connect(&socket_path).unwrap());
+    let timestamp = FixedOffset::east_opt(0)
+        .unwrap()
+        .with_ymd_and_hms(2024, 6, 1, 9, 5, 3)
+        .unwrap();
+    logger.push(
+        LogEntry::new(timestamp, LogLevel::Error, "disk full").with_fields(&[("mount", "/")]),
+    );
+    logger.push(LogEntry::new(timestamp, LogLevel::Warn, "disk filling"));
+
+    let mut buffer = [0; 1024];
+    let received = |buffer: &mut [u8]| {
+        let len = daemon.recv(buffer).unwrap();
+        String::from_utf8(buffer[..len].to_vec()).unwrap()
+    };
+    let error = received(&mut buffer);
+    let warn = received(&mut buffer);
+
+    // user facility (1) * 8 + LOG_ERR (3) / LOG_WARNING (4)
+    assert!(error.starts_with("<11>Jun  1 09:05:03 "), "{error}");
+    assert!(error.ends_with(&format!("[{}]: disk full mount=/", std::process::id())));
+    assert!(warn.starts_with("<12>Jun  1 09:05:03 "), "{warn}");
+    logger.clear();
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "webhook")]
+
+use simple_logger::{LogLevel, Logger, WebhookSink};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+/// Accepts one request and returns its body.
+fn receive_one(listener: TcpListener) -> String {
+    let (stream, _) = listener.accept().unwrap();
+    let mut reader = BufReader::new(stream);
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).unwrap();
+        if header == "\r\n" {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap();
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).unwrap();
+    reader
+        .get_mut()
+        .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+        .unwrap();
+    String::from_utf8(body).unwrap()
+}
+
+#[test]
+fn errors_are_batched_into_one_post() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/alerts", listener.local_addr().unwrap());
+    let server = thread::spawn(move || receive_one(listener));
+
+    let mut logger = Logger::builder().build();
+    logger.add_sink(WebhookSink::new(&url, LogLevel::Error));
+    logger.info("not an alert");
+    logger.error("database down");
+    logger.error("queue backing up");
+    // Dropping the logger waits for the pending request.
+    logger.clear();
+    drop(logger);
+
+    let body: serde_json::Value = serde_json::from_str(&server.join().unwrap()).unwrap();
+    let entries = body["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["message"], "database down");
+    assert_eq!(entries[1]["level"], "ERROR");
+    assert!(body["text"]
+        .as_str()
+        .unwrap()
+        .ends_with("ERROR: queue backing up"));
+}